@@ -20,4 +20,78 @@ pub enum BiddingError {
     InsufficientFunds,
     #[msg("Bid amount unchanged from existing bid")]
     NoBidChange,
+    #[msg("Auction end time must be after its start time and in the future")]
+    InvalidAuctionWindow,
+    #[msg("Auction has not started yet")]
+    AuctionNotStarted,
+    #[msg("Auction bidding window has ended")]
+    AuctionEnded,
+    #[msg("Auction bidding window has not ended yet")]
+    AuctionNotEnded,
+    #[msg("Soft close extension settings are invalid")]
+    InvalidExtensionConfig,
+    #[msg("Hidden reserve reveal is missing or does not match the commitment")]
+    InvalidReserveReveal,
+    #[msg("Bid does not beat the top bid by the minimum increment")]
+    BidIncrementTooLow,
+    #[msg("Buy-it-now price must not be below the reserve price")]
+    InvalidBuyNowPrice,
+    #[msg("Buy-it-now is not enabled for this auction")]
+    BuyNowDisabled,
+    #[msg("Dutch auction settings are invalid")]
+    InvalidDutchConfig,
+    #[msg("Instruction not supported for this auction kind")]
+    InvalidAuctionKind,
+    #[msg("Current price is above the buyer's limit")]
+    PriceAboveLimit,
+    #[msg("Sealed auction settings are invalid")]
+    InvalidSealedConfig,
+    #[msg("Reveal phase is not open")]
+    RevealNotOpen,
+    #[msg("Bid has already been revealed")]
+    BidAlreadyRevealed,
+    #[msg("Revealed amount and salt do not match the commitment")]
+    InvalidBidReveal,
+    #[msg("Revealed amount exceeds the escrowed deposit")]
+    BidExceedsDeposit,
+    #[msg("Auction has not been concluded yet")]
+    AuctionNotConcluded,
+    #[msg("The winning bid is claimed with claim_item")]
+    WinningBidNotRefundable,
+    #[msg("Bid accounts must be passed as [bid, bidder] groups of this auction, with their token escrows for SPL payments")]
+    InvalidBidAccounts,
+    #[msg("Keeper bounty exceeds the maximum share of the sale price")]
+    InvalidKeeperBounty,
+    #[msg("The winning bid account is required to pay the keeper bounty")]
+    WinningBidRequired,
+    #[msg("Grace period must be at least MIN_GRACE_PERIOD_SECS (60 seconds)")]
+    InvalidGracePeriod,
+    #[msg("Grace period after the auction could be settled has not elapsed")]
+    GracePeriodNotElapsed,
+    #[msg("Auctioned token amount must be greater than zero")]
+    InvalidTokenAmount,
+    #[msg("SPL token payments are only supported for English auctions without buy-it-now")]
+    InvalidPaymentConfig,
+    #[msg("Payment token accounts are required for auctions paid in an SPL token")]
+    PaymentAccountsMissing,
+    #[msg("Royalties must be at most 100% and split between at most 5 distinct creators whose shares sum to 100")]
+    InvalidRoyalties,
+    #[msg("Resales paying creator royalties must settle through conclude_auction (no buy-it-now or Dutch)")]
+    InvalidRoyaltyConfig,
+    #[msg("The token's Metaplex metadata account is required to pay creator royalties")]
+    MetadataRequired,
+    #[msg("Metadata account is not the Metaplex metadata of the auctioned mint")]
+    InvalidMetadataAccount,
+    #[msg("Every verified creator's wallet, and payment ATA for token payments, must be passed in the remaining accounts")]
+    CreatorAccountMissing,
+    #[msg("Collection mint, metadata and master edition must be passed together")]
+    CollectionAccountsMissing,
+    #[msg("An auction with bids cannot be cancelled by its owner")]
+    AuctionHasBids,
+    #[msg("A bid has already reached the buy-it-now price")]
+    BuyNowOutbid,
+    #[msg("The neighbouring bids in the ranking must be passed in the remaining accounts")]
+    RankingAccountsMissing,
+    #[msg("An auction whose bids can be concluded cannot be force-cancelled")]
+    AuctionConcludable,
 }
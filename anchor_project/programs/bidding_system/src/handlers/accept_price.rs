@@ -0,0 +1,135 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, AuctionRecord};
+use crate::utils::{release_vault, TokenMint, AUCTION_KIND_DUTCH};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AcceptPrice<'info>>,
+    max_lamports: u64,
+) -> Result<()> {
+    let auction_key = ctx.accounts.auction.key();
+    let price = {
+        let auction = ctx.accounts.auction.load()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        auction.require_kind(AUCTION_KIND_DUTCH)?;
+        auction.require_bidding_open(now)?;
+
+        let price = auction.dutch_price(now);
+
+        // Protect the buyer from paying more than they signed for
+        require!(price <= max_lamports, BiddingError::PriceAboveLimit);
+
+        ctx.accounts.auction_record.record(
+            auction_key,
+            &auction,
+            ctx.accounts.buyer.key(),
+            price,
+            now,
+            ctx.bumps.auction_record,
+        );
+        price
+    };
+
+    let buyer = ctx.accounts.buyer.key();
+
+    // Pay the owner directly from the buyer
+    require!(
+        ctx.accounts.buyer.lamports() >= price,
+        BiddingError::InsufficientFunds
+    );
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        price,
+    )?;
+
+    msg!("Dutch auction won by {} at {} lamports", buyer, price);
+
+    // Transfer token from the vault to buyer (auction PDA signs for its vault)
+    release_vault(
+        &TokenMint::new(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        ),
+        &ctx.accounts.vault,
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.auction,
+        ctx.bumps.auction,
+    )?;
+
+    // Auction account will be closed by close constraint, rent returned to owner
+    msg!("Auction closed, rent returned to owner");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptPrice<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = token_mint,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump,
+        close = owner
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Auctioned mint, receives the transfer fees withheld in the vault when it closes
+    #[account(mut, mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault holding the auctioned token, owned by the auction PDA
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = token_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Auction owner receiving the sale price
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Provenance record of the sale, paid by the buyer and kept after the auction closes
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + AuctionRecord::INIT_SPACE,
+        seeds = [b"auction_record", auction.key().as_ref()],
+        bump
+    )]
+    pub auction_record: Account<'info, AuctionRecord>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
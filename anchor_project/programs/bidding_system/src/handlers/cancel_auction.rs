@@ -1,58 +1,74 @@
 use crate::error::BiddingError;
 use crate::state::Auction;
-use crate::utils::MAX_BIDS;
+use crate::utils::{burn_vault, release_vault, TokenMint};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::associated_token::{
+    create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-pub fn process(ctx: Context<CancelAuction>) -> Result<()> {
-    let auction = ctx.accounts.auction.load()?;
+pub fn process<'info>(ctx: Context<'_, '_, 'info, 'info, CancelAuction<'info>>) -> Result<()> {
+    let minted = {
+        let auction = ctx.accounts.auction.load()?;
+        auction.require_pending()?;
 
-    // Check if the auction is concluded - conclude_auction is the only operation allowed if max bids reached
-    require!(
-        (auction.bid_count as usize) < MAX_BIDS,
-        BiddingError::AuctionConcluded
-    );
+        // Once a bid (or sealed commitment) exists the seller can no longer walk away,
+        // which also keeps them from front-running conclude_auction after end_ts
+        require!(auction.bid_count == 0, BiddingError::AuctionHasBids);
+        auction.minted == 1
+    };
 
     msg!("Auction cancelled: {}", ctx.accounts.auction.key());
 
-    // Burn the minted token
-    token::burn(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Burn {
-                mint: ctx.accounts.token_mint.to_account_info(),
-                from: ctx.accounts.owner_token_account.to_account_info(),
+    if minted {
+        // Burn the minted token (auction PDA signs for its vault)
+        burn_vault(
+            &TokenMint::new(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint,
+                ctx.remaining_accounts,
+            ),
+            &ctx.accounts.vault,
+            ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.auction,
+            ctx.bumps.auction,
+        )?;
+
+        msg!("Token burned");
+    } else {
+        // Deposited tokens go back to the owner's token account
+        create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.owner.to_account_info(),
+                associated_token: ctx.accounts.owner_token_account.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
             },
-        ),
-        1, // Burn 1 token
-    )?;
-
-    msg!("Token burned");
-
-    // Refund all bidders by transferring lamports from auction account
-    for bid in auction.bids.iter() {
-        if bid.bidder != Pubkey::default() {
-            **ctx
-                .accounts
-                .auction
-                .to_account_info()
-                .try_borrow_mut_lamports()? -= bid.lamports;
+        ))?;
 
-            // Create account info for bidder and transfer lamports
-            let bidder_account = ctx
-                .remaining_accounts
-                .iter()
-                .find(|acc| acc.key() == bid.bidder)
-                .ok_or(BiddingError::NoBidFound)?;
+        release_vault(
+            &TokenMint::new(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint,
+                ctx.remaining_accounts,
+            ),
+            &ctx.accounts.vault,
+            ctx.accounts.owner_token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.auction,
+            ctx.bumps.auction,
+        )?;
 
-            **bidder_account.try_borrow_mut_lamports()? += bid.lamports;
-            msg!("Refunded {} lamports to {}", bid.lamports, bid.bidder);
-        }
+        msg!("Token returned to owner");
     }
 
-    // Auction account will be closed by close constraint, rent returned to owner
-    msg!("All bids refunded, auction closed, rent returned to owner");
+    ctx.accounts
+        .auction
+        .close(ctx.accounts.owner.to_account_info())?;
+    msg!("Auction closed, rent returned to owner");
 
     Ok(())
 }
@@ -63,25 +79,43 @@ pub struct CancelAuction<'info> {
         mut,
         has_one = owner,
         has_one = token_mint,
-        seeds = [b"auction", auction.load()?.token_mint.as_ref()],
-        bump,
-        close = owner
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
     )]
     pub auction: AccountLoader<'info, Auction>,
 
-    #[account(mut)]
-    pub token_mint: Account<'info, Mint>,
+    /// Auctioned mint, receives the transfer fees withheld in the vault when it closes
+    #[account(mut, mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault holding the auctioned token, owned by the auction PDA
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    /// CHECK: Owner's associated token account, created in handler if deposited tokens are returned
     #[account(
         mut,
-        token::mint = token_mint,
-        token::authority = owner,
+        address = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &token_mint.key(),
+            &token_program.key()
+        )
     )]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -1,52 +1,50 @@
-use crate::error::BiddingError;
-use crate::state::Auction;
-use crate::utils::MAX_BIDS;
+use crate::state::{Auction, Bid};
+use crate::utils::{payment_account, BidRanking, TokenEscrow};
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-pub fn process(ctx: Context<CancelBid>) -> Result<()> {
+pub fn process<'info>(ctx: Context<'_, '_, 'info, 'info, CancelBid<'info>>) -> Result<()> {
     let mut auction = ctx.accounts.auction.load_mut()?;
     let bidder = ctx.accounts.bidder.key();
 
-    // Check if the auction is concluded - conclude_auction is the only operation allowed if max bids reached
-    require!(
-        (auction.bid_count as usize) < MAX_BIDS,
-        BiddingError::AuctionConcluded
-    );
+    // Bids can only be withdrawn while the auction's time window is open
+    auction.require_bidding_open(Clock::get()?.unix_timestamp)?;
 
-    // Get bid amount before removing
-    let bid_amount = auction
-        .find_bid_index(&bidder)
-        .map(|idx| auction.bids[idx].lamports)
-        .ok_or(crate::error::BiddingError::NoBidFound)?;
+    // Unlink the bid from the ranking; a withdrawn leader hands the lead to the runner-up
+    let bid_amount = ctx.accounts.bid.escrow();
+    let mut ranking = BidRanking::from_accounts(
+        ctx.accounts.auction.key(),
+        &auction,
+        ctx.accounts.bid.key(),
+        ctx.remaining_accounts,
+    )?;
+    ranking.remove(&mut auction, &mut ctx.accounts.bid)?;
+    ranking.exit()?;
 
-    // Remove the bid
-    auction.remove_bid(&bidder)?;
-
-    // Refund lamports from auction account to bidder
-    **ctx
-        .accounts
-        .auction
-        .to_account_info()
-        .try_borrow_mut_lamports()? -= bid_amount;
-    **ctx
-        .accounts
-        .bidder
-        .to_account_info()
-        .try_borrow_mut_lamports()? += bid_amount;
+    auction.bid_count -= 1;
 
     msg!("Bid cancelled by {}", bidder);
-    msg!("Refunded {} lamports", bid_amount);
-    msg!("Remaining bids: {}", auction.bid_count);
 
-    if let Some(top_bid) = auction.get_top_bid() {
-        msg!(
-            "New top bid: {} lamports by {}",
-            top_bid.lamports,
-            top_bid.bidder
-        );
+    // Auctions paid in an SPL token refund the bid's token escrow before the bid closes
+    if auction.pays_in_tokens() {
+        let escrow = TokenEscrow::from_accounts(
+            ctx.accounts.payment_token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.bid_escrow.as_ref(),
+            ctx.remaining_accounts,
+        )?;
+        let bidder_tokens = payment_account(ctx.accounts.bidder_payment_account.as_ref())?;
+        let amount = escrow.drain(
+            &ctx.accounts.bid,
+            bidder_tokens.to_account_info(),
+            ctx.accounts.bidder.to_account_info(),
+        )?;
+        msg!("Refunded {} payment tokens", amount);
     } else {
-        msg!("No bids remaining");
+        // Bid account is closed by close constraint, escrow and rent returned to bidder
+        msg!("Refunded {} lamports", bid_amount);
     }
+    msg!("Remaining bids: {}", auction.bid_count);
 
     Ok(())
 }
@@ -55,11 +53,49 @@ pub fn process(ctx: Context<CancelBid>) -> Result<()> {
 pub struct CancelBid<'info> {
     #[account(
         mut,
-        seeds = [b"auction", auction.load()?.token_mint.as_ref()],
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
         bump
     )]
     pub auction: AccountLoader<'info, Auction>,
 
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid.bump,
+        close = bidder
+    )]
+    pub bid: Account<'info, Bid>,
+
     #[account(mut)]
     pub bidder: Signer<'info>,
+
+    /// SPL token bids are paid in, receives the fees withheld in the escrow (SPL payments only)
+    #[account(mut, address = auction.load()?.payment_mint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Bid PDA's payment token escrow (SPL payments only), drained and closed in handler
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bid_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Bidder's payment token account receiving the escrow (SPL payments only)
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = bidder,
+        token::token_program = payment_token_program,
+    )]
+    pub bidder_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
 }
@@ -0,0 +1,189 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, Bid};
+use crate::utils::{payment_account, release_vault, transfer_lamports, TokenEscrow, TokenMint};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimItem<'info>>) -> Result<()> {
+    let winner = ctx.accounts.winner.key();
+    let (price, bid_count, pays_in_tokens) = {
+        let mut auction = ctx.accounts.auction.load_mut()?;
+
+        auction.require_concluded()?;
+        require!(auction.is_winner(&winner), BiddingError::InvalidWinner);
+
+        auction.bid_count -= 1;
+        (
+            auction.sale_lamports,
+            auction.bid_count,
+            auction.pays_in_tokens(),
+        )
+    };
+
+    // Pay the clearing price (less any keeper bounty) from the winner's bid escrow to the owner
+    if pays_in_tokens {
+        let escrow = TokenEscrow::from_accounts(
+            ctx.accounts.payment_token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.bid_escrow.as_ref(),
+            ctx.remaining_accounts,
+        )?;
+        let owner_tokens = payment_account(ctx.accounts.owner_payment_account.as_ref())?;
+        let winner_tokens = payment_account(ctx.accounts.winner_payment_account.as_ref())?;
+
+        escrow.withdraw(&ctx.accounts.bid, owner_tokens.to_account_info(), price)?;
+        msg!("Transferred {} payment tokens to auction owner", price);
+
+        // Escrow above the price goes back to the winner, the escrow rent as well
+        let refund = escrow.drain(
+            &ctx.accounts.bid,
+            winner_tokens.to_account_info(),
+            ctx.accounts.winner.to_account_info(),
+        )?;
+        msg!("Refunded {} payment tokens to {}", refund, winner);
+    } else {
+        transfer_lamports(
+            &ctx.accounts.bid.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            price,
+        )?;
+
+        msg!("Transferred {} lamports to auction owner", price);
+    }
+
+    // Transfer token from the vault to winner (auction PDA signs for its vault)
+    release_vault(
+        &TokenMint::new(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        ),
+        &ctx.accounts.vault,
+        ctx.accounts.winner_token_account.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.auction,
+        ctx.bumps.auction,
+    )?;
+
+    msg!("Token claimed by {}", winner);
+
+    // Bid account is closed by close constraint, escrow above the price and rent returned to winner
+    msg!(
+        "Refunded {} lamports to {}",
+        ctx.accounts.bid.to_account_info().lamports(),
+        winner
+    );
+
+    // The last claim closes the auction
+    if bid_count == 0 {
+        ctx.accounts
+            .auction
+            .close(ctx.accounts.owner.to_account_info())?;
+        msg!("Auction closed, rent returned to owner");
+    } else {
+        msg!("Remaining bids: {}", bid_count);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimItem<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = token_mint,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    #[account(
+        mut,
+        has_one = auction,
+        constraint = bid.bidder == winner.key() @ BiddingError::InvalidWinner,
+        seeds = [b"bid", auction.key().as_ref(), winner.key().as_ref()],
+        bump = bid.bump,
+        close = winner
+    )]
+    pub bid: Account<'info, Bid>,
+
+    /// Auctioned mint, receives the transfer fees withheld in the vault when it closes
+    #[account(mut, mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault holding the auctioned token, owned by the auction PDA
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Winner's token account receiving the item, created by the payer if needed
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = winner,
+        associated_token::token_program = token_program,
+    )]
+    pub winner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Auction owner receiving the sale price and the auction rent
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    /// Winning bidder receiving the item - anyone may claim it on their behalf
+    #[account(mut, address = auction.load()?.top_bidder @ BiddingError::InvalidWinner)]
+    pub winner: SystemAccount<'info>,
+
+    /// Pays for the token accounts created by the claim
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// SPL token bids are paid in, receives the fees withheld in the escrow (SPL payments only)
+    #[account(mut, address = auction.load()?.payment_mint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Winning bid's payment token escrow (SPL payments only), drained and closed in handler
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bid_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Owner's payment token account receiving the price (SPL payments only)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub owner_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Winner's payment token account receiving the escrow above the price (SPL payments only)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = winner,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub winner_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program of the auctioned mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the payment mint (SPL payments only)
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
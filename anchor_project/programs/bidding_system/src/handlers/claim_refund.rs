@@ -0,0 +1,106 @@
+use crate::state::{Auction, Bid};
+use crate::utils::{payment_account, refund_bid, TokenEscrow};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRefund<'info>>) -> Result<()> {
+    let bid_count = {
+        let mut auction = ctx.accounts.auction.load_mut()?;
+
+        // Refunds open once the auction has been settled
+        auction.require_concluded()?;
+
+        // Auctions paid in an SPL token also refund the bid's token escrow
+        let token_refund = if auction.pays_in_tokens() {
+            Some((
+                TokenEscrow::from_accounts(
+                    ctx.accounts.payment_token_program.as_ref(),
+                    ctx.accounts.payment_mint.as_ref(),
+                    ctx.accounts.bid_escrow.as_ref(),
+                    ctx.remaining_accounts,
+                )?,
+                payment_account(ctx.accounts.bidder_payment_account.as_ref())?.to_account_info(),
+            ))
+        } else {
+            None
+        };
+
+        refund_bid(
+            &mut auction,
+            &ctx.accounts.bid,
+            &ctx.accounts.bidder.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            token_refund,
+        )?;
+        auction.bid_count
+    };
+
+    // The last claim closes the auction
+    if bid_count == 0 {
+        ctx.accounts
+            .auction
+            .close(ctx.accounts.owner.to_account_info())?;
+        msg!("Auction closed, rent returned to owner");
+    } else {
+        msg!("Remaining bids: {}", bid_count);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Bid account, closed to the bidder in handler
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, Bid>,
+
+    /// Bidder receiving the refund - anyone may claim it on their behalf
+    #[account(mut)]
+    pub bidder: SystemAccount<'info>,
+
+    /// Auction owner receiving forfeited deposits and the auction rent
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    /// SPL token bids are paid in, receives the fees withheld in the escrow (SPL payments only)
+    #[account(mut, address = auction.load()?.payment_mint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Bid PDA's payment token escrow (SPL payments only), drained and closed in handler
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bid_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Bidder's payment token account receiving the escrow (SPL payments only)
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = bidder,
+        token::token_program = payment_token_program,
+    )]
+    pub bidder_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+}
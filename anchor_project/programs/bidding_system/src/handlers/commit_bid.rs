@@ -0,0 +1,98 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, Bid};
+use crate::utils::{transfer_lamports, AUCTION_KIND_SEALED};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, System};
+
+pub fn process(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+    require!(deposit > 0, BiddingError::InvalidBidAmount);
+
+    let bidder = ctx.accounts.bidder.key();
+    let mut auction = ctx.accounts.auction.load_mut()?;
+
+    auction.require_kind(AUCTION_KIND_SEALED)?;
+
+    // Commitments are only accepted inside the auction's time window
+    auction.require_bidding_open(Clock::get()?.unix_timestamp)?;
+
+    let bid = &mut ctx.accounts.bid;
+    let is_new_bid = bid.bidder == Pubkey::default();
+    let existing_deposit = bid.lamports;
+
+    if deposit > existing_deposit {
+        // Larger deposit - transfer additional lamports from bidder to the bid account
+        let additional_lamports = deposit - existing_deposit;
+
+        require!(
+            ctx.accounts.bidder.lamports() >= additional_lamports,
+            BiddingError::InsufficientFunds
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.bidder.to_account_info(),
+                    to: bid.to_account_info(),
+                },
+            ),
+            additional_lamports,
+        )?;
+    } else if deposit < existing_deposit {
+        // Smaller deposit - refund excess lamports from the bid account back to bidder
+        transfer_lamports(
+            &bid.to_account_info(),
+            &ctx.accounts.bidder.to_account_info(),
+            existing_deposit - deposit,
+        )?;
+    }
+
+    if is_new_bid {
+        bid.auction = ctx.accounts.auction.key();
+        bid.bidder = bidder;
+        bid.bump = ctx.bumps.bid;
+        auction.bid_count += 1;
+    }
+
+    // Record the deposit and the (replaced) commitment
+    bid.lamports = deposit;
+    bid.commitment = commitment;
+
+    msg!(
+        "Sealed bid committed by {} with {} lamports deposit",
+        bidder,
+        deposit
+    );
+    msg!("Total bids: {}", auction.bid_count);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Bidder's sealed bid, escrowing the deposit
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        space = 8 + Bid::INIT_SPACE,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bid: Account<'info, Bid>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
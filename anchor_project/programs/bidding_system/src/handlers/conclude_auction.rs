@@ -1,83 +1,203 @@
 use crate::error::BiddingError;
-use crate::state::Auction;
+use crate::state::{Auction, AuctionRecord, Bid, ReserveReveal};
+use crate::utils::{
+    can_receive_lamports, creator_account, payment_account, release_vault, transfer_lamports,
+    TokenEscrow, TokenMint, AUCTION_OUTCOME_BID_WON, AUCTION_OUTCOME_REFUND_ALL,
+};
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::associated_token::{
+    create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-pub fn process(ctx: Context<ConcludeAuction>) -> Result<()> {
-    let auction = ctx.accounts.auction.load()?;
+pub fn process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ConcludeAuction<'info>>,
+    reserve_reveal: Option<ReserveReveal>,
+) -> Result<()> {
+    let auction_key = ctx.accounts.auction.key();
+    let mut auction = ctx.accounts.auction.load_mut()?;
+
+    auction.require_pending()?;
+
+    // Nobody can end the sale before the announced closing time (or the reveal deadline)
+    let now = Clock::get()?.unix_timestamp;
+    require!(auction.can_settle(now), BiddingError::AuctionNotEnded);
 
     require!(auction.bid_count > 0, BiddingError::NoActiveBids);
 
-    let top_bid = auction.get_top_bid().ok_or(BiddingError::NoActiveBids)?;
+    let reserve = auction.resolve_reserve(reserve_reveal.as_ref(), now)?;
+    // A proxy leader bids up to the revealed reserve if its ceiling allows
+    auction.raise_top_to_reserve(reserve);
+
+    // Reserve not met (or no revealed bids) - return the token to the owner, every bid claims a refund
+    if !auction.has_top_bid() || auction.top_lamports < reserve {
+        if auction.has_top_bid() {
+            msg!(
+                "Reserve of {} lamports not met by top bid of {} lamports",
+                reserve,
+                auction.top_lamports
+            );
+        } else {
+            msg!("No revealed bids");
+        }
+        auction.outcome = AUCTION_OUTCOME_REFUND_ALL;
+        ctx.accounts.auction_record.record(
+            auction_key,
+            &auction,
+            Pubkey::default(),
+            0,
+            now,
+            ctx.bumps.auction_record,
+        );
+        msg!(
+            "Token returned to owner, {} bids to refund",
+            auction.bid_count
+        );
+        drop(auction);
+
+        // The caller pays for the owner's token account if it does not exist anymore
+        create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.cranker.to_account_info(),
+                associated_token: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
 
-    let winning_amount = top_bid.lamports;
-    let winner_key = top_bid.bidder;
+        release_vault(
+            &TokenMint::new(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint,
+                ctx.remaining_accounts,
+            ),
+            &ctx.accounts.vault,
+            ctx.accounts.owner_token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.auction,
+            ctx.bumps.auction,
+        )?;
+        return Ok(());
+    }
+
+    let winning_amount = auction.top_lamports;
+    let winner_key = auction.top_bidder;
+    let price = auction.clearing_price(reserve);
 
     msg!("Auction concluded!");
     msg!("Winner: {}", winner_key);
     msg!("Winning amount: {} lamports", winning_amount);
+    if price < winning_amount {
+        msg!("Second-price settlement: winner pays {} lamports", price);
+    }
 
-    // Verify the winner token account matches the actual winner
-    let expected_winner_token_account = anchor_spl::associated_token::get_associated_token_address(
-        &winner_key,
-        &ctx.accounts.token_mint.key(),
-    );
-    require!(
-        ctx.accounts.winner_token_account.key() == expected_winner_token_account,
-        BiddingError::InvalidWinner
-    );
+    // The creator royalties of a resale come out of the full price, the keeper bounty out of the
+    // owner's remainder, so a seller cranking their own auction cannot cut the royalties
+    let royalties = if auction.pays_royalties() {
+        auction.creator_royalties(price)
+    } else {
+        Vec::new()
+    };
+    let royalties_owed: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+    let bounty = auction.keeper_bounty(price).min(price - royalties_owed);
 
-    // Transfer token from owner to winner (owner signs)
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.owner_token_account.to_account_info(),
-                to: ctx.accounts.winner_token_account.to_account_info(),
-                authority: ctx.accounts.owner.to_account_info(),
-            },
-        ),
-        1, // Transfer 1 token
-    )?;
-
-    // Transfer winning bid amount from auction account to owner
-    **ctx
-        .accounts
-        .auction
-        .to_account_info()
-        .try_borrow_mut_lamports()? -= winning_amount;
-    **ctx
-        .accounts
-        .owner
-        .to_account_info()
-        .try_borrow_mut_lamports()? += winning_amount;
-
-    msg!("Transferred {} lamports to auction owner", winning_amount);
-
-    // Refund all other bidders by transferring lamports from auction account
-    for bid in auction.bids.iter() {
-        if bid.bidder != Pubkey::default() && bid.bidder != winner_key {
-            **ctx
-                .accounts
-                .auction
-                .to_account_info()
-                .try_borrow_mut_lamports()? -= bid.lamports;
-
-            // Find bidder account in remaining_accounts and transfer lamports
-            let bidder_account = ctx
-                .remaining_accounts
-                .iter()
-                .find(|acc| acc.key() == bid.bidder)
-                .ok_or(BiddingError::NoBidFound)?;
-
-            **bidder_account.try_borrow_mut_lamports()? += bid.lamports;
-            msg!("Refunded {} lamports to {}", bid.lamports, bid.bidder);
+    // A lamport royalty that cannot be delivered is skipped rather than blocking the sale, its
+    // share stays with the owner
+    let mut royalty_total = 0;
+    if bounty > 0 || !royalties.is_empty() {
+        let winning_bid = ctx
+            .accounts
+            .winning_bid
+            .as_ref()
+            .ok_or(BiddingError::WinningBidRequired)?;
+        if auction.pays_in_tokens() {
+            let escrow = TokenEscrow::from_accounts(
+                ctx.accounts.payment_token_program.as_ref(),
+                ctx.accounts.payment_mint.as_ref(),
+                ctx.accounts.winning_bid_escrow.as_ref(),
+                ctx.remaining_accounts,
+            )?;
+            if bounty > 0 {
+                let cranker_tokens =
+                    payment_account(ctx.accounts.cranker_payment_account.as_ref())?;
+                escrow.withdraw(winning_bid, cranker_tokens.to_account_info(), bounty)?;
+                msg!(
+                    "Keeper bounty of {} payment tokens paid to {}",
+                    bounty,
+                    ctx.accounts.cranker.key()
+                );
+            }
+            // Creators receive royalties in their payment ATA, created by the caller if missing
+            // so a token-paid resale cannot strip creators who never held the payment token
+            for (creator, amount) in &royalties {
+                let creator_wallet = creator_account(ctx.remaining_accounts, creator)?;
+                let creator_tokens = get_associated_token_address_with_program_id(
+                    creator,
+                    &auction.payment_mint,
+                    escrow.payment.token_program.key,
+                );
+                let creator_tokens = creator_account(ctx.remaining_accounts, &creator_tokens)?;
+                create_idempotent(CpiContext::new(
+                    ctx.accounts.associated_token_program.to_account_info(),
+                    Create {
+                        payer: ctx.accounts.cranker.to_account_info(),
+                        associated_token: creator_tokens.clone(),
+                        authority: creator_wallet.clone(),
+                        mint: escrow.payment.mint.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        token_program: escrow.payment.token_program.clone(),
+                    },
+                ))?;
+                escrow.withdraw(winning_bid, creator_tokens.clone(), *amount)?;
+                royalty_total += amount;
+                msg!("Royalty of {} payment tokens paid to {}", amount, creator);
+            }
+        } else {
+            if bounty > 0 {
+                transfer_lamports(
+                    &winning_bid.to_account_info(),
+                    &ctx.accounts.cranker.to_account_info(),
+                    bounty,
+                )?;
+                msg!(
+                    "Keeper bounty of {} lamports paid to {}",
+                    bounty,
+                    ctx.accounts.cranker.key()
+                );
+            }
+            for (creator, amount) in &royalties {
+                let creator_info = creator_account(ctx.remaining_accounts, creator)?;
+                if !can_receive_lamports(creator_info, *amount)? {
+                    msg!(
+                        "Royalty of {} skipped, {} cannot hold it rent-exempt",
+                        amount,
+                        creator
+                    );
+                    continue;
+                }
+                transfer_lamports(&winning_bid.to_account_info(), creator_info, *amount)?;
+                royalty_total += amount;
+                msg!("Royalty of {} lamports paid to {}", amount, creator);
+            }
         }
     }
 
-    // Auction account will be closed by close constraint, rent returned to owner
-    msg!("Auction closed, rent returned to owner");
+    // The price is paid to the owner when the winner claims the token from the vault
+    auction.outcome = AUCTION_OUTCOME_BID_WON;
+    auction.sale_lamports = price - royalty_total - bounty;
+    ctx.accounts.auction_record.record(
+        auction_key,
+        &auction,
+        winner_key,
+        price,
+        now,
+        ctx.bumps.auction_record,
+    );
+
+    msg!("Token ready to be claimed by the winner, other bids claim a refund");
 
     Ok(())
 }
@@ -88,31 +208,92 @@ pub struct ConcludeAuction<'info> {
         mut,
         has_one = owner,
         has_one = token_mint,
-        seeds = [b"auction", auction.load()?.token_mint.as_ref()],
-        bump,
-        close = owner
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
     )]
     pub auction: AccountLoader<'info, Auction>,
 
-    #[account(mut)]
-    pub token_mint: Account<'info, Mint>,
+    /// Bid of the top bidder, paying the keeper bounty and royalties (only needed when owed)
+    #[account(
+        mut,
+        has_one = auction,
+        seeds = [b"bid", auction.key().as_ref(), auction.load()?.top_bidder.as_ref()],
+        bump = winning_bid.bump
+    )]
+    pub winning_bid: Option<Account<'info, Bid>>,
+
+    /// SPL token bids are paid in (SPL payments only)
+    #[account(address = auction.load()?.payment_mint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Winning bid's payment token escrow, paying the bounty and royalties (SPL payments only)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = winning_bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub winning_bid_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Cranker's payment token account receiving the bounty (SPL payments only)
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = cranker,
+        token::token_program = payment_token_program,
+    )]
+    pub cranker_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Auctioned mint, receives the transfer fees withheld in the vault when it closes
+    #[account(mut, mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
 
+    /// Vault holding the auctioned token, owned by the auction PDA
     #[account(
         mut,
-        token::mint = token_mint,
-        token::authority = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
     )]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: Winner token account - validated to match actual winner's ATA in handler
-    /// Must be initialized before calling this instruction
+    /// CHECK: Owner's associated token account, created in handler only if the token is returned
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &token_mint.key(),
+            &token_program.key()
+        )
+    )]
+    pub owner_token_account: UncheckedAccount<'info>,
+
+    /// Auction owner receiving the token back when no bid wins, and the vault rent
     #[account(mut)]
-    pub winner_token_account: UncheckedAccount<'info>,
+    pub owner: SystemAccount<'info>,
 
+    /// Anyone concluding the ended auction, paid the keeper bounty
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub cranker: Signer<'info>,
+
+    /// Provenance record of the outcome, paid by the cranker and kept after the auction closes
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + AuctionRecord::INIT_SPACE,
+        seeds = [b"auction_record", auction.key().as_ref()],
+        bump
+    )]
+    pub auction_record: Account<'info, AuctionRecord>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token program of the auctioned mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the payment mint (SPL payments only)
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
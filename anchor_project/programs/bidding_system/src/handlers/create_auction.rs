@@ -1,30 +1,75 @@
-use crate::state::Auction;
+use crate::error::BiddingError;
+use crate::state::{Auction, AuctionConfig, AuctionCounter, Royalties};
+use crate::utils::{AUCTION_KIND_DUTCH, SETTLEMENT_SECOND_PRICE};
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::metadata::{
-    create_metadata_accounts_v3, mpl_token_metadata::types::DataV2, CreateMetadataAccountsV3,
-    Metadata,
+    create_metadata_accounts_v3,
+    mpl_token_metadata::{
+        instructions::CreateMasterEditionV3CpiBuilder,
+        types::{Collection, Creator, DataV2},
+    },
+    verify_sized_collection_item, CreateMetadataAccountsV3, Metadata, VerifySizedCollectionItem,
 };
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use anchor_spl::token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface};
 
 pub fn process(
     ctx: Context<CreateAuction>,
     name: String,
     symbol: String,
     uri: String,
+    config: AuctionConfig,
+    royalties: Royalties,
 ) -> Result<()> {
-    let auction = &mut ctx.accounts.auction.load_init()?;
-    auction.initialize(ctx.accounts.owner.key(), ctx.accounts.mint.key());
+    config.validate(Clock::get()?.unix_timestamp)?;
+    royalties.validate()?;
 
-    // Mint exactly one token to the owner
+    // The collection is optional, but all of its accounts come together
+    let collection = match (
+        &ctx.accounts.collection_mint,
+        &ctx.accounts.collection_metadata,
+        &ctx.accounts.collection_master_edition,
+    ) {
+        (Some(mint), Some(metadata), Some(master_edition)) => Some((
+            mint.to_account_info(),
+            metadata.to_account_info(),
+            master_edition.to_account_info(),
+        )),
+        (None, None, None) => None,
+        _ => return err!(BiddingError::CollectionAccountsMissing),
+    };
+
+    // Each auction of the mint takes the next nonce from its counter
+    let nonce = ctx
+        .accounts
+        .auction_counter
+        .next_nonce(ctx.accounts.mint.key(), ctx.bumps.auction_counter);
+
+    // This first sale is the primary sale, every later auction of the mint pays royalties
+    ctx.accounts.auction_counter.pays_royalties = true;
+
+    let mut auction = ctx.accounts.auction.load_init()?;
+    auction.initialize(
+        ctx.accounts.owner.key(),
+        ctx.accounts.mint.key(),
+        nonce,
+        1,
+        true,
+        &config,
+    );
+    if let Some(payment_mint) = &ctx.accounts.payment_mint {
+        auction.set_payment_mint(payment_mint.key())?;
+    }
+
+    // Mint exactly one token into the vault owned by the auction PDA
     let cpi_accounts = MintTo {
         mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.token_account.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
         authority: ctx.accounts.owner.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::mint_to(cpi_ctx, 1)?;
+    token_interface::mint_to(cpi_ctx, 1)?;
 
     // Create metadata with description in the name/uri
     let metadata_ctx = CpiContext::new(
@@ -40,33 +85,134 @@ pub fn process(
         },
     );
 
+    // The owner signs as update authority, so only they can be listed as verified right away;
+    // other creators verify themselves with Metaplex to start receiving royalties
+    let creators = (!royalties.creators.is_empty()).then(|| {
+        royalties
+            .creators
+            .iter()
+            .map(|creator| Creator {
+                address: creator.address,
+                verified: creator.address == ctx.accounts.owner.key(),
+                share: creator.share,
+            })
+            .collect()
+    });
+
     let data_v2 = DataV2 {
         name,
         symbol,
         uri,
-        seller_fee_basis_points: 0,
-        creators: None,
-        collection: None,
+        seller_fee_basis_points: royalties.seller_fee_basis_points,
+        creators,
+        collection: collection
+            .as_ref()
+            .map(|(collection_mint, _, _)| Collection {
+                verified: false,
+                key: collection_mint.key(),
+            }),
         uses: None,
     };
 
     create_metadata_accounts_v3(metadata_ctx, data_v2, true, true, None)?;
 
+    // A master edition with max supply 0 takes over the mint and freeze authorities,
+    // so no further copy of the token can ever be minted (built directly to pass the
+    // mint's own token program, anchor-spl's helper assumes the legacy one)
+    CreateMasterEditionV3CpiBuilder::new(&ctx.accounts.metadata_program.to_account_info())
+        .edition(&ctx.accounts.master_edition.to_account_info())
+        .mint(&ctx.accounts.mint.to_account_info())
+        .update_authority(&ctx.accounts.owner.to_account_info())
+        .mint_authority(&ctx.accounts.owner.to_account_info())
+        .payer(&ctx.accounts.owner.to_account_info())
+        .metadata(&ctx.accounts.metadata.to_account_info())
+        .token_program(&ctx.accounts.token_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .rent(Some(&ctx.accounts.rent.to_account_info()))
+        .max_supply(0)
+        .invoke()?;
+
+    // The owner, as update authority of the collection, verifies the item into it
+    if let Some((collection_mint, collection_metadata, collection_master_edition)) = collection {
+        verify_sized_collection_item(
+            CpiContext::new(
+                ctx.accounts.metadata_program.to_account_info(),
+                VerifySizedCollectionItem {
+                    payer: ctx.accounts.owner.to_account_info(),
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    collection_authority: ctx.accounts.owner.to_account_info(),
+                    collection_mint,
+                    collection_metadata,
+                    collection_master_edition,
+                },
+            ),
+            None,
+        )?;
+    }
+
     msg!("Auction created: {}", ctx.accounts.auction.key());
-    msg!("Token mint: {}", auction.token_mint);
+    msg!(
+        "Token mint: {} (auction #{})",
+        auction.token_mint,
+        auction.nonce
+    );
+    msg!("Master edition: {}", ctx.accounts.master_edition.key());
+    if let Some(collection_mint) = &ctx.accounts.collection_mint {
+        msg!("Verified in collection {}", collection_mint.key());
+    }
     msg!("Owner: {}", auction.owner);
+    msg!("Bidding window: {} - {}", auction.start_ts, auction.end_ts);
+    if auction.pays_in_tokens() {
+        msg!("Bids paid in {}", auction.payment_mint);
+    }
+    if auction.extension_window_secs > 0 {
+        msg!(
+            "Soft close: +{}s for bids in last {}s, capped at {}",
+            auction.extension_secs,
+            auction.extension_window_secs,
+            auction.max_end_ts
+        );
+    }
+
+    if auction.buy_now_lamports > 0 {
+        msg!("Buy-it-now price: {} lamports", auction.buy_now_lamports);
+    }
+    if auction.kind == AUCTION_KIND_DUTCH {
+        msg!(
+            "Dutch auction: {} -> {} lamports",
+            auction.dutch_start_price,
+            auction.dutch_floor_price
+        );
+    }
+    if auction.settlement_mode == SETTLEMENT_SECOND_PRICE {
+        msg!("Second-price settlement");
+    }
 
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(name: String, symbol: String, uri: String)]
+#[instruction(name: String, symbol: String, uri: String, config: AuctionConfig)]
 pub struct CreateAuction<'info> {
+    /// Per-mint auction sequence, created with the first auction of the mint
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + AuctionCounter::INIT_SPACE,
+        seeds = [b"auction_counter", mint.key().as_ref()],
+        bump
+    )]
+    pub auction_counter: Account<'info, AuctionCounter>,
+
     #[account(
         init,
         payer = owner,
         space = 8 + std::mem::size_of::<Auction>(),
-        seeds = [b"auction", mint.key().as_ref()],
+        seeds = [
+            b"auction",
+            mint.key().as_ref(),
+            &auction_counter.count.to_le_bytes()
+        ],
         bump
     )]
     pub auction: AccountLoader<'info, Auction>,
@@ -76,25 +222,58 @@ pub struct CreateAuction<'info> {
         payer = owner,
         mint::decimals = 0,
         mint::authority = owner,
+        mint::freeze_authority = owner,
+        mint::token_program = token_program,
     )]
-    pub mint: Account<'info, Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Validated by Metadata program
     #[account(mut)]
     pub metadata: UncheckedAccount<'info>,
 
+    /// CHECK: Master edition PDA, initialized by the Metadata program
     #[account(
-        init_if_needed,
+        mut,
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// Vault holding the auctioned token until settlement, owned by the auction PDA
+    #[account(
+        init,
         payer = owner,
         associated_token::mint = mint,
-        associated_token::authority = owner,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
     )]
-    pub token_account: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL token bids are paid in, lamports if omitted
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Mint of the owner's collection NFT the token is verified into, validated by
+    /// Metadata program (optional, together with the two collection accounts below)
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Sized collection's metadata, validated by Metadata program
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection's master edition, validated by Metadata program
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token program of the minted token, the legacy token program or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
@@ -0,0 +1,147 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, AuctionConfig, AuctionCounter};
+use crate::utils::{verified_creators, TokenMint};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateAuctionForExistingMint<'info>>,
+    amount: u64,
+    config: AuctionConfig,
+) -> Result<()> {
+    config.validate(Clock::get()?.unix_timestamp)?;
+    require!(amount > 0, BiddingError::InvalidTokenAmount);
+
+    // Each auction of the mint takes the next nonce from its counter
+    let nonce = ctx
+        .accounts
+        .auction_counter
+        .next_nonce(ctx.accounts.token_mint.key(), ctx.bumps.auction_counter);
+
+    // Deposit the owner's tokens into the vault owned by the auction PDA
+    let asset = TokenMint::new(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_mint,
+        ctx.remaining_accounts,
+    );
+    asset.transfer(
+        ctx.accounts.owner_token_account.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        amount,
+        &[],
+    )?;
+
+    // A transfer fee is withheld from the deposit, so auction what actually arrived
+    ctx.accounts.vault.reload()?;
+    let deposited = ctx.accounts.vault.amount;
+    require!(deposited > 0, BiddingError::InvalidTokenAmount);
+
+    let mut auction = ctx.accounts.auction.load_init()?;
+    auction.initialize(
+        ctx.accounts.owner.key(),
+        ctx.accounts.token_mint.key(),
+        nonce,
+        deposited,
+        false,
+        &config,
+    );
+    if let Some(payment_mint) = &ctx.accounts.payment_mint {
+        auction.set_payment_mint(payment_mint.key())?;
+    }
+    // Reselling a token minted by create_auction pays its creators at conclusion, at the rate
+    // and to the verified creators its metadata lists now
+    if ctx.accounts.auction_counter.pays_royalties {
+        let metadata = ctx
+            .accounts
+            .metadata
+            .as_ref()
+            .ok_or(BiddingError::MetadataRequired)?;
+        let (royalty_bps, creators) = verified_creators(metadata, &auction.token_mint)?;
+        auction.enable_royalties(royalty_bps, &creators)?;
+    }
+
+    msg!("Auction created: {}", ctx.accounts.auction.key());
+    msg!(
+        "Token mint: {} ({} decimals, auction #{})",
+        auction.token_mint,
+        ctx.accounts.token_mint.decimals,
+        auction.nonce
+    );
+    msg!("Deposited amount: {}", auction.token_amount);
+    msg!("Owner: {}", auction.owner);
+    msg!("Bidding window: {} - {}", auction.start_ts, auction.end_ts);
+    if auction.pays_in_tokens() {
+        msg!("Bids paid in {}", auction.payment_mint);
+    }
+    if auction.pays_royalties() {
+        msg!("Resale: creator royalties paid at conclusion");
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, config: AuctionConfig)]
+pub struct CreateAuctionForExistingMint<'info> {
+    /// Per-mint auction sequence, created with the first auction of the mint
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + AuctionCounter::INIT_SPACE,
+        seeds = [b"auction_counter", token_mint.key().as_ref()],
+        bump
+    )]
+    pub auction_counter: Account<'info, AuctionCounter>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Auction>(),
+        seeds = [
+            b"auction",
+            token_mint.key().as_ref(),
+            &auction_counter.count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's token account the auctioned tokens are deposited from
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault holding the auctioned tokens until settlement, owned by the auction PDA
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL token bids are paid in, lamports if omitted
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Metaplex metadata of the mint, validated and read in the handler to snapshot the
+    /// creator royalties (resales of tokens minted by create_auction only)
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token program of the auctioned mint, the legacy token program or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,180 @@
+use crate::state::{Auction, AuctionConfig, AuctionCounter};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{
+    self, spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_2022::instruction::AuthorityType, spl_token_metadata_interface::state::TokenMetadata,
+    token_metadata_initialize, Mint, MintTo, SetAuthority, TokenAccount, TokenMetadataInitialize,
+};
+
+pub fn process(
+    ctx: Context<CreateAuctionWithTokenMetadata>,
+    name: String,
+    symbol: String,
+    uri: String,
+    config: AuctionConfig,
+) -> Result<()> {
+    config.validate(Clock::get()?.unix_timestamp)?;
+
+    // Each auction of the mint takes the next nonce from its counter
+    let nonce = ctx
+        .accounts
+        .auction_counter
+        .next_nonce(ctx.accounts.mint.key(), ctx.bumps.auction_counter);
+
+    let mut auction = ctx.accounts.auction.load_init()?;
+    auction.initialize(
+        ctx.accounts.owner.key(),
+        ctx.accounts.mint.key(),
+        nonce,
+        1,
+        true,
+        &config,
+    );
+    if let Some(payment_mint) = &ctx.accounts.payment_mint {
+        auction.set_payment_mint(payment_mint.key())?;
+    }
+
+    // The metadata grows the mint account, so top up its rent before Token-2022 reallocates it
+    let token_metadata = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(ctx.accounts.owner.key()))?,
+        mint: ctx.accounts.mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let mint_len = mint_info.data_len() + token_metadata.tlv_size_of()?;
+    let rent_top_up = Rent::get()?
+        .minimum_balance(mint_len)
+        .saturating_sub(mint_info.lamports());
+    if rent_top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: mint_info.clone(),
+                },
+            ),
+            rent_top_up,
+        )?;
+    }
+
+    // Store name, symbol and uri on the mint itself (its metadata pointer points at the mint)
+    token_metadata_initialize(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TokenMetadataInitialize {
+                program_id: ctx.accounts.token_program.to_account_info(),
+                metadata: mint_info.clone(),
+                update_authority: ctx.accounts.owner.to_account_info(),
+                mint_authority: ctx.accounts.owner.to_account_info(),
+                mint: mint_info,
+            },
+        ),
+        name,
+        symbol,
+        uri,
+    )?;
+
+    // Mint exactly one token into the vault owned by the auction PDA
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token_interface::mint_to(cpi_ctx, 1)?;
+
+    // Revoke the mint authority so no further copy can ever be minted (the mint is created
+    // without a freeze authority, so the token cannot be frozen either)
+    token_interface::set_authority(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.owner.to_account_info(),
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+            },
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    msg!("Auction created: {}", ctx.accounts.auction.key());
+    msg!(
+        "Token-2022 mint with on-mint metadata: {} (auction #{}, mint authority revoked)",
+        auction.token_mint,
+        auction.nonce
+    );
+    msg!("Owner: {}", auction.owner);
+    msg!("Bidding window: {} - {}", auction.start_ts, auction.end_ts);
+    if auction.pays_in_tokens() {
+        msg!("Bids paid in {}", auction.payment_mint);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, symbol: String, uri: String, config: AuctionConfig)]
+pub struct CreateAuctionWithTokenMetadata<'info> {
+    /// Per-mint auction sequence, created with the first auction of the mint
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + AuctionCounter::INIT_SPACE,
+        seeds = [b"auction_counter", mint.key().as_ref()],
+        bump
+    )]
+    pub auction_counter: Account<'info, AuctionCounter>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Auction>(),
+        seeds = [
+            b"auction",
+            mint.key().as_ref(),
+            &auction_counter.count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Token-2022 mint carrying its own metadata (metadata pointer to itself)
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = owner,
+        mint::token_program = token_program,
+        extensions::metadata_pointer::authority = owner,
+        extensions::metadata_pointer::metadata_address = mint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault holding the auctioned token until settlement, owned by the auction PDA
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL token bids are paid in, lamports if omitted
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{
+    create_metadata_accounts_v3,
+    mpl_token_metadata::{
+        instructions::CreateMasterEditionV3CpiBuilder,
+        types::{CollectionDetails, DataV2},
+    },
+    CreateMetadataAccountsV3, Metadata,
+};
+use anchor_spl::token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface};
+
+pub fn process(
+    ctx: Context<CreateCollection>,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    // The seller keeps the collection NFT in their own token account
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.collection_mint.to_account_info(),
+        to: ctx.accounts.owner_token_account.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token_interface::mint_to(cpi_ctx, 1)?;
+
+    // Sized collection metadata, counting the items verified into it
+    let metadata_ctx = CpiContext::new(
+        ctx.accounts.metadata_program.to_account_info(),
+        CreateMetadataAccountsV3 {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            mint: ctx.accounts.collection_mint.to_account_info(),
+            mint_authority: ctx.accounts.owner.to_account_info(),
+            payer: ctx.accounts.owner.to_account_info(),
+            update_authority: ctx.accounts.owner.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        },
+    );
+
+    let data_v2 = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+
+    create_metadata_accounts_v3(
+        metadata_ctx,
+        data_v2,
+        true,
+        true,
+        Some(CollectionDetails::V1 { size: 0 }),
+    )?;
+
+    // The collection NFT is a 1-of-1 too, its master edition holds the mint authority
+    CreateMasterEditionV3CpiBuilder::new(&ctx.accounts.metadata_program.to_account_info())
+        .edition(&ctx.accounts.master_edition.to_account_info())
+        .mint(&ctx.accounts.collection_mint.to_account_info())
+        .update_authority(&ctx.accounts.owner.to_account_info())
+        .mint_authority(&ctx.accounts.owner.to_account_info())
+        .payer(&ctx.accounts.owner.to_account_info())
+        .metadata(&ctx.accounts.metadata.to_account_info())
+        .token_program(&ctx.accounts.token_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .rent(Some(&ctx.accounts.rent.to_account_info()))
+        .max_supply(0)
+        .invoke()?;
+
+    msg!("Collection created: {}", ctx.accounts.collection_mint.key());
+    msg!("Owner: {}", ctx.accounts.owner.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateCollection<'info> {
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = owner,
+        mint::freeze_authority = owner,
+        mint::token_program = token_program,
+    )]
+    pub collection_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Validated by Metadata program
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master edition PDA, initialized by the Metadata program
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            collection_mint.key().as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// Owner's token account holding the collection NFT
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = collection_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seller owning the collection, its update authority for verifying auctioned items
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Token program of the collection mint, the legacy token program or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,120 @@
+use crate::error::BiddingError;
+use crate::state::Auction;
+use crate::utils::{release_vault, TokenMint};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{
+    create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(ctx: Context<'_, '_, 'info, 'info, ForceCancelAuction<'info>>) -> Result<()> {
+    {
+        let auction = ctx.accounts.auction.load()?;
+        auction.require_pending()?;
+
+        // Only once nobody settled the auction within its grace period
+        require!(
+            auction.grace_elapsed(Clock::get()?.unix_timestamp),
+            BiddingError::GracePeriodNotElapsed
+        );
+
+        // Only an auction without bids cannot be concluded; bids must be concluded instead, so
+        // the owner cannot take the item back
+        require!(auction.bid_count == 0, BiddingError::AuctionConcludable);
+    }
+
+    msg!(
+        "Auction force-cancelled by {}: {}",
+        ctx.accounts.caller.key(),
+        ctx.accounts.auction.key()
+    );
+
+    // The caller pays for the owner's token account if it does not exist anymore
+    create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        Create {
+            payer: ctx.accounts.caller.to_account_info(),
+            associated_token: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    // Return the token from the vault to the owner (auction PDA signs for its vault)
+    release_vault(
+        &TokenMint::new(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        ),
+        &ctx.accounts.vault,
+        ctx.accounts.owner_token_account.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.auction,
+        ctx.bumps.auction,
+    )?;
+
+    msg!("Token returned to owner");
+
+    // Without bids nothing is left to refund
+    ctx.accounts
+        .auction
+        .close(ctx.accounts.owner.to_account_info())?;
+    msg!("Auction closed, rent returned to owner");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ForceCancelAuction<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = token_mint,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Auctioned mint, receives the transfer fees withheld in the vault when it closes
+    #[account(mut, mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault holding the auctioned token, owned by the auction PDA
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Owner's associated token account, created in handler if missing
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &token_mint.key(),
+            &token_program.key()
+        )
+    )]
+    pub owner_token_account: UncheckedAccount<'info>,
+
+    /// Auction owner receiving the token back and the vault rent
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    /// Anyone returning the token of a bidless auction its owner left open past the grace period
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
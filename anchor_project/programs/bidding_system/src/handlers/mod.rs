@@ -1,11 +1,35 @@
+pub mod accept_price;
+pub mod buy_now;
 pub mod cancel_auction;
 pub mod cancel_bid;
+pub mod claim_item;
+pub mod claim_refund;
+pub mod commit_bid;
 pub mod conclude_auction;
 pub mod create_auction;
+pub mod create_auction_for_existing_mint;
+pub mod create_auction_with_token_metadata;
+pub mod create_collection;
+pub mod force_cancel_auction;
 pub mod place_bid;
+pub mod place_proxy_bid;
+pub mod reveal_bid;
+pub mod settle_batch;
 
+pub use accept_price::*;
+pub use buy_now::*;
 pub use cancel_auction::*;
 pub use cancel_bid::*;
+pub use claim_item::*;
+pub use claim_refund::*;
+pub use commit_bid::*;
 pub use conclude_auction::*;
 pub use create_auction::*;
+pub use create_auction_for_existing_mint::*;
+pub use create_auction_with_token_metadata::*;
+pub use create_collection::*;
+pub use force_cancel_auction::*;
 pub use place_bid::*;
+pub use place_proxy_bid::*;
+pub use reveal_bid::*;
+pub use settle_batch::*;
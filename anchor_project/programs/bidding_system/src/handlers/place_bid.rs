@@ -1,90 +1,37 @@
-use crate::error::BiddingError;
-use crate::state::Auction;
-use crate::utils::MAX_BIDS;
+use crate::state::{Auction, Bid};
+use crate::utils::{escrow_and_rank, BidPlacement};
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{self, System};
-
-pub fn process(ctx: Context<PlaceBid>, lamports: u64) -> Result<()> {
-    require!(lamports > 0, BiddingError::InvalidBidAmount);
-
-    let bidder = ctx.accounts.bidder.key();
-
-    // Get existing bid and check constraints
-    let existing_bid = {
-        let auction = ctx.accounts.auction.load()?;
-
-        // Check if the auction is concluded
-        require!(
-            (auction.bid_count as usize) < MAX_BIDS,
-            BiddingError::AuctionConcluded
-        );
-
-        auction
-            .find_bid_index(&bidder)
-            .map(|idx| auction.bids[idx].lamports)
-            .unwrap_or(0)
-    };
-
-    // Make sure that there's an actual change
-    require!(lamports != existing_bid, BiddingError::NoBidChange);
-
-    if lamports > existing_bid {
-        // Increasing bid - transfer additional lamports from bidder to auction account
-        let additional_lamports = lamports - existing_bid;
-
-        require!(
-            **ctx.accounts.bidder.to_account_info().lamports.borrow() >= additional_lamports,
-            BiddingError::InsufficientFunds
-        );
-
-        let auction_info = ctx.accounts.auction.to_account_info();
-        let bidder_info = ctx.accounts.bidder.to_account_info();
-        let system_program_info = ctx.accounts.system_program.to_account_info();
-
-        system_program::transfer(
-            CpiContext::new(
-                system_program_info,
-                system_program::Transfer {
-                    from: bidder_info,
-                    to: auction_info,
-                },
-            ),
-            additional_lamports,
-        )?;
-    } else {
-        // Decreasing bid - refund excess lamports from auction account back to bidder
-        let refund_amount = existing_bid - lamports;
-        **ctx
-            .accounts
-            .auction
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= refund_amount;
-        **ctx
-            .accounts
-            .bidder
-            .to_account_info()
-            .try_borrow_mut_lamports()? += refund_amount;
-    }
-
-    // Now update the bid in auction state
-    let mut auction = ctx.accounts.auction.load_mut()?;
-    let max_reached = auction.upsert_bid(bidder, lamports)?;
-
-    msg!("Bid placed by {}: {} lamports", bidder, lamports);
-    msg!("Total bids: {}", auction.bid_count);
-
-    if let Some(top) = auction.get_top_bid() {
-        msg!(
-            "Current top bid: {} lamports by {}",
-            top.lamports,
-            top.bidder
-        );
-    }
-
-    // Signal when max bids reached - owner must call conclude_auction
-    if max_reached {
-        msg!("Max bids reached! Auction concluded. Awaiting owner action.");
-    }
+use anchor_lang::system_program::System;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlaceBid<'info>>,
+    lamports: u64,
+) -> Result<()> {
+    // A plain bid replaces any proxy bid
+    escrow_and_rank(
+        BidPlacement {
+            auction: &ctx.accounts.auction,
+            bid: &mut ctx.accounts.bid,
+            bid_bump: ctx.bumps.bid,
+            bidder: &ctx.accounts.bidder,
+            payment_mint: ctx.accounts.payment_mint.as_ref(),
+            bid_escrow: ctx.accounts.bid_escrow.as_ref(),
+            bidder_payment_account: ctx.accounts.bidder_payment_account.as_ref(),
+            payment_token_program: ctx.accounts.payment_token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        lamports,
+        0,
+    )?;
+
+    msg!(
+        "Bid placed by {}: {} lamports",
+        ctx.accounts.bidder.key(),
+        lamports
+    );
 
     Ok(())
 }
@@ -93,13 +40,52 @@ pub fn process(ctx: Context<PlaceBid>, lamports: u64) -> Result<()> {
 pub struct PlaceBid<'info> {
     #[account(
         mut,
-        seeds = [b"auction", auction.load()?.token_mint.as_ref()],
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
         bump
     )]
     pub auction: AccountLoader<'info, Auction>,
 
+    /// Bidder's bid, escrowing the bid amount
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        space = 8 + Bid::INIT_SPACE,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bid: Account<'info, Bid>,
+
     #[account(mut)]
     pub bidder: Signer<'info>,
 
+    /// SPL token bids are paid in (SPL payments only)
+    #[account(address = auction.load()?.payment_mint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Bid PDA's payment token escrow (SPL payments only)
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bid_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Bidder's payment token account (SPL payments only)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bidder,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bidder_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
     pub system_program: Program<'info, System>,
 }
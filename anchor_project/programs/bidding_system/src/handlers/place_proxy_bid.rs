@@ -0,0 +1,91 @@
+use crate::state::{Auction, Bid};
+use crate::utils::{escrow_and_rank, BidPlacement};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::System;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlaceProxyBid<'info>>,
+    max_lamports: u64,
+) -> Result<()> {
+    // Store the ceiling and let the program work out the visible amount
+    escrow_and_rank(
+        BidPlacement {
+            auction: &ctx.accounts.auction,
+            bid: &mut ctx.accounts.bid,
+            bid_bump: ctx.bumps.bid,
+            bidder: &ctx.accounts.bidder,
+            payment_mint: ctx.accounts.payment_mint.as_ref(),
+            bid_escrow: ctx.accounts.bid_escrow.as_ref(),
+            bidder_payment_account: ctx.accounts.bidder_payment_account.as_ref(),
+            payment_token_program: ctx.accounts.payment_token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        0,
+        max_lamports,
+    )?;
+
+    msg!(
+        "Proxy bid placed by {}: up to {} lamports",
+        ctx.accounts.bidder.key(),
+        max_lamports
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PlaceProxyBid<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Bidder's bid, escrowing the full ceiling
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        space = 8 + Bid::INIT_SPACE,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bid: Account<'info, Bid>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// SPL token bids are paid in (SPL payments only)
+    #[account(address = auction.load()?.payment_mint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Bid PDA's payment token escrow (SPL payments only)
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bid_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Bidder's payment token account (SPL payments only)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bidder,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bidder_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Program<'info, System>,
+}
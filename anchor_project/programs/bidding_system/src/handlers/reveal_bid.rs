@@ -0,0 +1,80 @@
+use crate::error::BiddingError;
+use crate::state::{commitment_hash, Auction, Bid};
+use crate::utils::{transfer_lamports, BidRanking, AUCTION_KIND_SEALED};
+use anchor_lang::prelude::*;
+
+pub fn process(ctx: Context<RevealBid>, lamports: u64, salt: [u8; 32]) -> Result<()> {
+    let mut auction = ctx.accounts.auction.load_mut()?;
+    let bidder = ctx.accounts.bidder.key();
+
+    auction.require_kind(AUCTION_KIND_SEALED)?;
+    auction.require_reveal_open(Clock::get()?.unix_timestamp)?;
+
+    let bid = &mut ctx.accounts.bid;
+
+    require!(!bid.revealed, BiddingError::BidAlreadyRevealed);
+    require!(
+        commitment_hash(lamports, &salt) == bid.commitment,
+        BiddingError::InvalidBidReveal
+    );
+    require!(lamports > 0, BiddingError::InvalidBidAmount);
+    require!(lamports <= bid.lamports, BiddingError::BidExceedsDeposit);
+
+    // Refund the part of the deposit above the revealed amount
+    let excess = bid.lamports - lamports;
+    transfer_lamports(
+        &bid.to_account_info(),
+        &ctx.accounts.bidder.to_account_info(),
+        excess,
+    )?;
+
+    // The bid now escrows exactly the revealed amount
+    bid.lamports = lamports;
+    bid.revealed = true;
+
+    // Rank the revealed bid, relinking the neighbouring bids passed in the remaining accounts
+    let mut ranking = BidRanking::from_accounts(
+        ctx.accounts.auction.key(),
+        &auction,
+        bid.key(),
+        ctx.remaining_accounts,
+    )?;
+    ranking.insert(&mut auction, bid)?;
+    ranking.exit()?;
+
+    msg!("Bid revealed by {}: {} lamports", bidder, lamports);
+    msg!("Refunded {} lamports of excess deposit", excess);
+    msg!(
+        "Current top revealed bid: {} lamports by {}",
+        auction.top_lamports,
+        auction.top_bidder
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, Bid>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+}
@@ -0,0 +1,129 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, Bid};
+use crate::utils::{payment_account, refund_bid, TokenEscrow};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub fn process<'info>(ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>) -> Result<()> {
+    let auction_key = ctx.accounts.auction.key();
+    let bid_count = {
+        let mut auction = ctx.accounts.auction.load_mut()?;
+
+        auction.require_concluded()?;
+
+        // A page of bids to refund, passed as [bid, bidder] pairs in remaining_accounts
+        // Auctions paid in an SPL token pass [bid, bidder, bid escrow, bidder payment account]
+        // groups instead, followed by the payment mint's transfer hook accounts (if any)
+        let pays_in_tokens = auction.pays_in_tokens();
+        let group_len = if pays_in_tokens { 4 } else { 2 };
+        let accounts = ctx.remaining_accounts;
+        let bid_accounts = accounts
+            .chunks_exact(group_len)
+            .take_while(|group| !pays_in_tokens || group[0].owner == &crate::ID)
+            .count()
+            * group_len;
+        require!(
+            bid_accounts > 0 && (pays_in_tokens || bid_accounts == accounts.len()),
+            BiddingError::InvalidBidAccounts
+        );
+
+        for group in accounts[..bid_accounts].chunks_exact(group_len) {
+            // A bid closed earlier in the page no longer deserializes, so duplicates fail here
+            let bid = Account::<Bid>::try_from(&group[0])?;
+            require!(
+                bid.auction == auction_key && *group[1].key == bid.bidder,
+                BiddingError::InvalidBidAccounts
+            );
+
+            let token_refund = if pays_in_tokens {
+                // The escrow must be the bid PDA's payment ATA itself: draining a decoy would
+                // close the bid and leave the real escrow with no signer to empty it
+                let payment_token_program = ctx
+                    .accounts
+                    .payment_token_program
+                    .as_ref()
+                    .ok_or_else(|| error!(BiddingError::PaymentAccountsMissing))?;
+                let escrow_address = get_associated_token_address_with_program_id(
+                    &bid.key(),
+                    &auction.payment_mint,
+                    payment_token_program.key,
+                );
+                let bid_escrow = InterfaceAccount::<TokenAccount>::try_from(&group[2])?;
+                let bidder_tokens = InterfaceAccount::<TokenAccount>::try_from(&group[3])?;
+                require!(
+                    *group[2].key == escrow_address
+                        && bid_escrow.owner == bid.key()
+                        && bid_escrow.mint == auction.payment_mint
+                        && bidder_tokens.owner == bid.bidder
+                        && bidder_tokens.mint == auction.payment_mint,
+                    BiddingError::InvalidBidAccounts
+                );
+                Some((
+                    TokenEscrow::from_accounts(
+                        ctx.accounts.payment_token_program.as_ref(),
+                        ctx.accounts.payment_mint.as_ref(),
+                        Some(&bid_escrow),
+                        &accounts[bid_accounts..],
+                    )?,
+                    payment_account(Some(&bidder_tokens))?.to_account_info(),
+                ))
+            } else {
+                None
+            };
+
+            refund_bid(
+                &mut auction,
+                &bid,
+                &group[1],
+                &ctx.accounts.owner.to_account_info(),
+                token_refund,
+            )?;
+        }
+
+        // bid_count doubles as the settlement cursor: the bids still waiting for a page
+        auction.bid_count
+    };
+
+    // The final page closes the auction
+    if bid_count == 0 {
+        ctx.accounts
+            .auction
+            .close(ctx.accounts.owner.to_account_info())?;
+        msg!("Auction closed, rent returned to owner");
+    } else {
+        msg!("Remaining bids: {}", bid_count);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [
+            b"auction",
+            auction.load()?.token_mint.as_ref(),
+            &auction.load()?.nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub auction: AccountLoader<'info, Auction>,
+
+    /// Auction owner receiving forfeited deposits and the auction rent
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    /// SPL token bids are paid in, receives the fees withheld in the escrows (SPL payments only)
+    #[account(
+        mut,
+        address = auction.load()?.payment_mint,
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Token program of the payment mint (SPL payments only)
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+}
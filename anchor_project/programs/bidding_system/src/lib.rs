@@ -11,38 +11,149 @@ pub mod state;
 pub mod utils;
 
 use handlers::*;
+use state::{AuctionConfig, ReserveReveal, Royalties};
 
 #[program]
 pub mod bidding_system {
     use super::*;
 
-    /// Create a new auction with a token containing metadata
+    /// Create a new auction with a token containing metadata, configured by `config`; the
+    /// metadata lists `royalties`, paid to the verified creators whenever the token is resold,
+    /// and the token is verified into the owner's collection when its accounts are passed
     pub fn create_auction(
         ctx: Context<CreateAuction>,
         name: String,
         symbol: String,
         uri: String,
+        config: AuctionConfig,
+        royalties: Royalties,
     ) -> Result<()> {
-        handlers::create_auction::process(ctx, name, symbol, uri)
+        handlers::create_auction::process(ctx, name, symbol, uri, config, royalties)
+    }
+
+    /// Create an auction for `amount` tokens of an existing mint (e.g. an NFT the owner holds),
+    /// deposited from the owner's token account into the auction's vault
+    pub fn create_auction_for_existing_mint<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateAuctionForExistingMint<'info>>,
+        amount: u64,
+        config: AuctionConfig,
+    ) -> Result<()> {
+        handlers::create_auction_for_existing_mint::process(ctx, amount, config)
+    }
+
+    /// Create a new auction like `create_auction`, but with a Token-2022 mint storing its name,
+    /// symbol and uri in its own token-metadata extension instead of Metaplex metadata
+    pub fn create_auction_with_token_metadata(
+        ctx: Context<CreateAuctionWithTokenMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+        config: AuctionConfig,
+    ) -> Result<()> {
+        handlers::create_auction_with_token_metadata::process(ctx, name, symbol, uri, config)
+    }
+
+    /// Create a sized Metaplex collection NFT held by the owner, which auctioned tokens can be
+    /// verified into by passing its accounts to `create_auction`
+    pub fn create_collection(
+        ctx: Context<CreateCollection>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        handlers::create_collection::process(ctx, name, symbol, uri)
     }
 
     /// Place a bid on an auction (replaces previous bid from same address if exists)
-    pub fn place_bid(ctx: Context<PlaceBid>, lamports: u64) -> Result<()> {
+    /// Late bids extend the end time when soft close is configured
+    pub fn place_bid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBid<'info>>,
+        lamports: u64,
+    ) -> Result<()> {
         handlers::place_bid::process(ctx, lamports)
     }
 
-    /// Cancel a bid
-    pub fn cancel_bid(ctx: Context<CancelBid>) -> Result<()> {
+    /// Place a proxy bid: escrow max_lamports and let the program outbid others up to it
+    pub fn place_proxy_bid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceProxyBid<'info>>,
+        max_lamports: u64,
+    ) -> Result<()> {
+        handlers::place_proxy_bid::process(ctx, max_lamports)
+    }
+
+    /// Cancel a bid, closing its bid account; a withdrawn leader hands the lead to the runner-up
+    pub fn cancel_bid<'info>(ctx: Context<'_, '_, 'info, 'info, CancelBid<'info>>) -> Result<()> {
         handlers::cancel_bid::process(ctx)
     }
 
-    /// Conclude an auction and determine winner
-    pub fn conclude_auction(ctx: Context<ConcludeAuction>) -> Result<()> {
-        handlers::conclude_auction::process(ctx)
+    /// Commit a sealed bid `hash(amount || salt)` with an escrowed deposit (replaces previous commitment)
+    pub fn commit_bid(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+        handlers::commit_bid::process(ctx, commitment, deposit)
+    }
+
+    /// Reveal a sealed bid during the reveal phase, refunding the deposit above the amount
+    pub fn reveal_bid(ctx: Context<RevealBid>, lamports: u64, salt: [u8; 32]) -> Result<()> {
+        handlers::reveal_bid::process(ctx, lamports, salt)
     }
 
-    /// Cancel an active auction
-    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+    /// Conclude an auction and record its outcome (only after end_ts)
+    /// Anyone may call it and earn the keeper bounty out of the sale price
+    /// Hidden reserves must be revealed; if the reserve is not met every bid claims a refund
+    /// The winner and price are kept in the auction's AuctionRecord for provenance
+    pub fn conclude_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConcludeAuction<'info>>,
+        reserve_reveal: Option<ReserveReveal>,
+    ) -> Result<()> {
+        handlers::conclude_auction::process(ctx, reserve_reveal)
+    }
+
+    /// Deliver the token to the winner of a concluded auction, paying the clearing price to the owner
+    /// Anyone may claim it on the winner's behalf
+    pub fn claim_item<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimItem<'info>>) -> Result<()> {
+        handlers::claim_item::process(ctx)
+    }
+
+    /// Refund a losing bid of a concluded or cancelled auction, closing its bid account
+    /// The last claim closes the auction
+    pub fn claim_refund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimRefund<'info>>,
+    ) -> Result<()> {
+        handlers::claim_refund::process(ctx)
+    }
+
+    /// Refund a page of losing bids, passed as `[bid, bidder]` pairs in remaining_accounts
+    /// The final page closes the auction
+    pub fn settle_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>,
+    ) -> Result<()> {
+        handlers::settle_batch::process(ctx)
+    }
+
+    /// Buy the token at the buy-it-now price; existing bids claim a refund
+    pub fn buy_now<'info>(ctx: Context<'_, '_, 'info, 'info, BuyNow<'info>>) -> Result<()> {
+        handlers::buy_now::process(ctx)
+    }
+
+    /// Buy a Dutch auction's token at the current decaying price (at most max_lamports)
+    pub fn accept_price<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptPrice<'info>>,
+        max_lamports: u64,
+    ) -> Result<()> {
+        handlers::accept_price::process(ctx, max_lamports)
+    }
+
+    /// Cancel an active auction that has no bids, closing it
+    pub fn cancel_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelAuction<'info>>,
+    ) -> Result<()> {
         handlers::cancel_auction::process(ctx)
     }
+
+    /// Cancel an auction nobody can conclude (no bids) once its grace period has passed (anyone);
+    /// the token returns to the owner
+    pub fn force_cancel_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ForceCancelAuction<'info>>,
+    ) -> Result<()> {
+        handlers::force_cancel_auction::process(ctx)
+    }
 }
@@ -1,141 +1,737 @@
-use crate::utils::MAX_BIDS;
+use crate::error::BiddingError;
+use crate::utils::{
+    AUCTION_KIND_DUTCH, AUCTION_KIND_ENGLISH, AUCTION_KIND_SEALED, AUCTION_OUTCOME_BID_WON,
+    AUCTION_OUTCOME_PENDING, BPS_DENOMINATOR, MAX_CREATORS, MAX_KEEPER_BOUNTY_BPS,
+    MIN_GRACE_PERIOD_SECS, RESERVE_HIDDEN, RESERVE_NONE, RESERVE_PUBLIC, SETTLEMENT_FIRST_PRICE,
+    SETTLEMENT_SECOND_PRICE,
+};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// Minimum price the owner is willing to accept
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ReservePrice {
+    /// Any top bid wins
+    None,
+    /// Reserve visible to everyone
+    Public { lamports: u64 },
+    /// Only `hash(reserve_lamports_le || salt)` is stored; revealed at conclusion
+    Hidden { commitment: [u8; 32] },
+}
+
+/// Price discovery mechanism of an auction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AuctionKind {
+    /// Ascending bids escrowed in per-bidder bid accounts
+    English,
+    /// Descending price from start_price at start_ts to floor_price, held for the final step
+    /// before end_ts; the first accept_price at the current price wins. step_secs = 0 decays
+    /// linearly, otherwise the price drops once every step_secs
+    Dutch {
+        start_price: u64,
+        floor_price: u64,
+        step_secs: i64,
+    },
+    /// Sealed first-price bids: bidders commit `hash(amount || salt)` with a deposit
+    /// between start_ts and end_ts, then reveal until reveal_end_ts. Unrevealed deposits
+    /// go to the owner if forfeit_unrevealed is set, otherwise they are refunded
+    Sealed {
+        reveal_end_ts: i64,
+        forfeit_unrevealed: bool,
+    },
+}
+
+/// Price the winning bidder pays at conclusion
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SettlementMode {
+    /// Winner pays their own bid
+    FirstPrice,
+    /// Vickrey: winner pays the second-highest bid, or the reserve if there is only one bid
+    /// (never less than the reserve); the difference is refunded at conclusion
+    SecondPrice,
+}
+
+/// Commitment to a hidden amount: sha256(lamports as little-endian bytes || salt)
+pub fn commitment_hash(lamports: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&lamports.to_le_bytes(), salt]).to_bytes()
+}
+
+/// Opening of a hidden reserve commitment, supplied to conclude_auction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ReserveReveal {
+    pub lamports: u64,
+    pub salt: [u8; 32],
+}
+
+impl ReserveReveal {
+    /// Commitment matching this reveal
+    pub fn commitment(&self) -> [u8; 32] {
+        commitment_hash(self.lamports, &self.salt)
+    }
+}
+
+/// Auction parameters supplied to create_auction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AuctionConfig {
+    /// Bidding opens at this unix timestamp
+    pub start_ts: i64,
+    /// Bidding closes at this unix timestamp (may be pushed back by late bids)
+    pub end_ts: i64,
+    /// Bids landing within this many seconds of end_ts extend the auction (0 disables soft close)
+    pub extension_window_secs: i64,
+    /// Seconds added to end_ts by each late bid
+    pub extension_secs: i64,
+    /// Hard cap that end_ts can never be extended past
+    pub max_end_ts: i64,
+    /// Optional reserve price, public or hash-committed
+    pub reserve: ReservePrice,
+    /// New or raised bids must beat the top bid by at least this many lamports
+    pub min_increment_lamports: u64,
+    /// New or raised bids must beat the top bid by at least this share of it (basis points)
+    pub min_increment_bps: u16,
+    /// Price at which anyone can settle the auction immediately (0 disables buy-it-now)
+    pub buy_now_lamports: u64,
+    /// English (ascending bids), Dutch (descending price) or Sealed (commit-reveal)
+    pub kind: AuctionKind,
+    /// First-price or second-price (Vickrey) settlement of English and sealed auctions
+    pub settlement: SettlementMode,
+    /// Share of the sale price paid to whoever concludes the auction (basis points)
+    pub keeper_bounty_bps: u16,
+    /// Seconds after the auction can be settled for the owner to reveal a hidden reserve, and before
+    /// anyone may force-cancel it without bids
+    pub grace_period_secs: i64,
+}
+
+impl AuctionConfig {
+    /// Validate the configuration against the current unix timestamp
+    pub fn validate(&self, now: i64) -> Result<()> {
+        require!(
+            self.end_ts > self.start_ts && self.end_ts > now,
+            BiddingError::InvalidAuctionWindow
+        );
+        require!(
+            self.extension_window_secs >= 0
+                && self.extension_secs >= 0
+                && self.max_end_ts >= self.end_ts,
+            BiddingError::InvalidExtensionConfig
+        );
+        require!(
+            self.grace_period_secs >= MIN_GRACE_PERIOD_SECS,
+            BiddingError::InvalidGracePeriod
+        );
+        require!(
+            self.keeper_bounty_bps <= MAX_KEEPER_BOUNTY_BPS,
+            BiddingError::InvalidKeeperBounty
+        );
+        if let ReservePrice::Public { lamports } = self.reserve {
+            require!(
+                self.buy_now_lamports == 0 || self.buy_now_lamports >= lamports,
+                BiddingError::InvalidBuyNowPrice
+            );
+        }
+        if let AuctionKind::Dutch {
+            start_price,
+            floor_price,
+            step_secs,
+        } = self.kind
+        {
+            // Dutch auctions take no bids, so bid-based features do not apply
+            require!(
+                floor_price > 0
+                    && start_price > floor_price
+                    && step_secs >= 0
+                    && step_secs < self.end_ts - self.start_ts
+                    && self.reserve == ReservePrice::None
+                    && self.buy_now_lamports == 0
+                    && self.extension_window_secs == 0
+                    && self.settlement == SettlementMode::FirstPrice,
+                BiddingError::InvalidDutchConfig
+            );
+        }
+        if let AuctionKind::Sealed { reveal_end_ts, .. } = self.kind {
+            // Sealed bids cannot be compared before reveal, so bid-driven features do not apply
+            require!(
+                reveal_end_ts > self.end_ts
+                    && self.buy_now_lamports == 0
+                    && self.extension_window_secs == 0,
+                BiddingError::InvalidSealedConfig
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Creator listed in a minted token's metadata, with their share of its royalties
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CreatorShare {
+    pub address: Pubkey,
+    /// Percentage of the royalties paid to this creator
+    pub share: u8,
+}
+
+/// Royalties written into the Metaplex metadata of a token minted by create_auction
+/// They are paid to the verified creators whenever the token is auctioned again
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Royalties {
+    /// Share of every resale price paid to the creators (basis points)
+    pub seller_fee_basis_points: u16,
+    /// Creators and their shares (summing to 100), empty for no royalties
+    pub creators: Vec<CreatorShare>,
+}
+
+impl Royalties {
+    /// Validate the royalties against the limits of Metaplex metadata
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.seller_fee_basis_points as u128 <= BPS_DENOMINATOR
+                && self.creators.len() <= MAX_CREATORS,
+            BiddingError::InvalidRoyalties
+        );
+        if self.creators.is_empty() {
+            require!(
+                self.seller_fee_basis_points == 0,
+                BiddingError::InvalidRoyalties
+            );
+            return Ok(());
+        }
+
+        let total_share: u32 = self.creators.iter().map(|c| c.share as u32).sum();
+        let unique = self
+            .creators
+            .iter()
+            .enumerate()
+            .all(|(i, c)| self.creators[..i].iter().all(|o| o.address != c.address));
+        require!(total_share == 100 && unique, BiddingError::InvalidRoyalties);
+        Ok(())
+    }
+}
 
 /// Main auction state account
+/// Bids live in their own PDAs; only the aggregates needed for settlement are kept here
 #[account(zero_copy)]
 #[repr(C)]
 #[derive(Default)]
 pub struct Auction {
-    pub owner: Pubkey,            // 32 bytes
-    pub token_mint: Pubkey,       // 32 bytes
-    pub bids: [Bid; MAX_BIDS],    // 48 * 10 = 480 bytes
-    pub bid_count: u8,            // 1 byte
-    pub next_insertion_index: u8, // 1 byte - tracks next insertion index to assign
-    pub padding: [u8; 6],         // 6 bytes padding for 64-bit alignment
+    pub owner: Pubkey,                            // 32 bytes
+    pub token_mint: Pubkey,                       // 32 bytes
+    pub top_bidder: Pubkey, // 32 bytes - leading bidder, default if there is none
+    pub top_lamports: u64,  // 8 bytes - leading bid (current price of a proxy leader)
+    pub top_ceiling: u64,   // 8 bytes - most the leader has escrowed (proxy ceiling)
+    pub second_lamports: u64, // 8 bytes - runner-up bid (or ceiling) in the ranking
+    pub bid_count: u32,     // 4 bytes - number of open bid accounts
+    pub padding: [u8; 4],   // 4 bytes padding for 64-bit alignment
+    pub start_ts: i64,      // 8 bytes - bidding opens at this unix timestamp
+    pub end_ts: i64,        // 8 bytes - bidding closes at this unix timestamp
+    pub extension_window_secs: i64, // 8 bytes - late-bid window that triggers soft close
+    pub extension_secs: i64, // 8 bytes - seconds added to end_ts per late bid
+    pub max_end_ts: i64,    // 8 bytes - hard cap for soft close extensions
+    pub reserve_lamports: u64, // 8 bytes - public reserve (RESERVE_PUBLIC only)
+    pub reserve_commitment: [u8; 32], // 32 bytes - hidden reserve hash (RESERVE_HIDDEN only)
+    pub reserve_kind: u8,   // 1 byte - RESERVE_NONE / RESERVE_PUBLIC / RESERVE_HIDDEN
+    pub padding2: [u8; 7],  // 7 bytes padding for 64-bit alignment
+    pub min_increment_lamports: u64, // 8 bytes - absolute minimum raise over the top bid
+    pub min_increment_bps: u16, // 2 bytes - relative minimum raise over the top bid
+    pub keeper_bounty_bps: u16, // 2 bytes - share of the sale price paid to the concluder
+    pub padding3: [u8; 4],  // 4 bytes padding for 64-bit alignment
+    pub buy_now_lamports: u64, // 8 bytes - immediate settlement price, 0 if disabled
+    pub kind: u8,           // 1 byte - AUCTION_KIND_ENGLISH / _DUTCH / _SEALED
+    pub forfeit_unrevealed: u8, // 1 byte - sealed: 1 if unrevealed deposits go to owner
+    pub settlement_mode: u8, // 1 byte - SETTLEMENT_FIRST_PRICE / SETTLEMENT_SECOND_PRICE
+    pub outcome: u8,        // 1 byte - AUCTION_OUTCOME_PENDING / _BID_WON / _REFUND_ALL
+    pub minted: u8,         // 1 byte - 1 if create_auction minted the token
+    pub royalties: u8,      // 1 byte - 1 if the sale pays the creators' royalties
+    pub padding4: [u8; 2],  // 2 bytes padding for 64-bit alignment
+    pub dutch_start_price: u64, // 8 bytes - Dutch price at start_ts
+    pub dutch_floor_price: u64, // 8 bytes - Dutch price for the final step
+    pub dutch_step_secs: i64, // 8 bytes - Dutch price step interval, 0 for linear decay
+    pub reveal_end_ts: i64, // 8 bytes - sealed: reveals close at this unix timestamp
+    pub sale_lamports: u64, // 8 bytes - proceeds owed to the owner by the winner
+    pub grace_period_secs: i64, // 8 bytes - hidden reserve reveal and force-cancel delay
+    pub token_amount: u64,  // 8 bytes - amount of token_mint auctioned from the vault
+    pub nonce: u64,         // 8 bytes - sequence number of this auction for token_mint
+    pub payment_mint: Pubkey, // 32 bytes - SPL token bids are paid in, default for lamports
+    pub royalty_bps: u16,   // 2 bytes - resale: seller_fee_basis_points at listing
+    pub royalty_creator_count: u8, // 1 byte - resale: number of verified creators at listing
+    pub royalty_shares: [u8; MAX_CREATORS], // 5 bytes - resale: share of each verified creator
+    pub royalty_creators: [Pubkey; MAX_CREATORS], // 160 bytes - resale: verified creators at listing
 }
 
 impl Auction {
-    /// Initialize a new auction with owner and token mint
-    pub fn initialize(&mut self, owner: Pubkey, token_mint: Pubkey) {
+    /// Initialize a new auction with owner, the auctioned tokens and configuration
+    pub fn initialize(
+        &mut self,
+        owner: Pubkey,
+        token_mint: Pubkey,
+        nonce: u64,
+        token_amount: u64,
+        minted: bool,
+        config: &AuctionConfig,
+    ) {
         self.owner = owner;
         self.token_mint = token_mint;
-        self.bids = [Bid::default(); MAX_BIDS];
+        self.nonce = nonce;
+        self.payment_mint = Pubkey::default();
+        self.token_amount = token_amount;
+        self.minted = minted as u8;
+        self.royalties = 0;
+        self.royalty_bps = 0;
+        self.royalty_creator_count = 0;
+        self.royalty_shares = [0u8; MAX_CREATORS];
+        self.royalty_creators = [Pubkey::default(); MAX_CREATORS];
+        self.top_bidder = Pubkey::default();
+        self.top_lamports = 0;
+        self.top_ceiling = 0;
+        self.second_lamports = 0;
         self.bid_count = 0;
-        self.next_insertion_index = 1; // Start at 1
-        self.padding = [0u8; 6];
+        self.padding = [0u8; 4];
+        self.start_ts = config.start_ts;
+        self.end_ts = config.end_ts;
+        self.extension_window_secs = config.extension_window_secs;
+        self.extension_secs = config.extension_secs;
+        self.max_end_ts = config.max_end_ts;
+        self.set_reserve(&config.reserve);
+        self.padding2 = [0u8; 7];
+        self.min_increment_lamports = config.min_increment_lamports;
+        self.min_increment_bps = config.min_increment_bps;
+        self.keeper_bounty_bps = config.keeper_bounty_bps;
+        self.padding3 = [0u8; 4];
+        self.buy_now_lamports = config.buy_now_lamports;
+        self.settlement_mode = match config.settlement {
+            SettlementMode::FirstPrice => SETTLEMENT_FIRST_PRICE,
+            SettlementMode::SecondPrice => SETTLEMENT_SECOND_PRICE,
+        };
+        self.outcome = AUCTION_OUTCOME_PENDING;
+        self.padding4 = [0u8; 2];
+        self.sale_lamports = 0;
+        self.grace_period_secs = config.grace_period_secs;
+        match config.kind {
+            AuctionKind::English => self.kind = AUCTION_KIND_ENGLISH,
+            AuctionKind::Dutch {
+                start_price,
+                floor_price,
+                step_secs,
+            } => {
+                self.kind = AUCTION_KIND_DUTCH;
+                self.dutch_start_price = start_price;
+                self.dutch_floor_price = floor_price;
+                self.dutch_step_secs = step_secs;
+            }
+            AuctionKind::Sealed {
+                reveal_end_ts,
+                forfeit_unrevealed,
+            } => {
+                self.kind = AUCTION_KIND_SEALED;
+                self.reveal_end_ts = reveal_end_ts;
+                self.forfeit_unrevealed = forfeit_unrevealed as u8;
+            }
+        }
     }
 
-    /// Check if a bid slot is empty (zero sentinel)
-    fn is_empty_bid(bid: &Bid) -> bool {
-        bid.bidder == Pubkey::default()
+    /// Pay bids in an SPL token instead of lamports (English auctions without buy-it-now only)
+    pub fn set_payment_mint(&mut self, payment_mint: Pubkey) -> Result<()> {
+        require!(
+            self.kind == AUCTION_KIND_ENGLISH && self.buy_now_lamports == 0,
+            BiddingError::InvalidPaymentConfig
+        );
+        self.payment_mint = payment_mint;
+        Ok(())
+    }
+
+    /// Pay creator royalties out of the sale price at conclusion, for resales of tokens minted by
+    /// create_auction (buy-it-now and Dutch sales settle without conclusion, so they are refused)
+    /// The rate and verified creators are snapshotted at listing, so later metadata updates cannot
+    /// change what this sale pays
+    pub fn enable_royalties(&mut self, royalty_bps: u16, creators: &[(Pubkey, u8)]) -> Result<()> {
+        require!(
+            self.kind != AUCTION_KIND_DUTCH && self.buy_now_lamports == 0,
+            BiddingError::InvalidRoyaltyConfig
+        );
+        require!(
+            creators.len() <= MAX_CREATORS,
+            BiddingError::InvalidMetadataAccount
+        );
+        self.royalties = 1;
+        self.royalty_bps = royalty_bps;
+        self.royalty_creator_count = creators.len() as u8;
+        for (i, (creator, share)) in creators.iter().enumerate() {
+            self.royalty_creators[i] = *creator;
+            self.royalty_shares[i] = *share;
+        }
+        Ok(())
     }
 
-    /// Find the index of a bid by bidder
-    pub fn find_bid_index(&self, bidder: &Pubkey) -> Option<usize> {
-        self.bids
+    /// Royalties owed to each creator snapshotted at listing out of the sale `price`
+    pub fn creator_royalties(&self, price: u64) -> Vec<(Pubkey, u64)> {
+        let royalty = price as u128 * self.royalty_bps as u128 / BPS_DENOMINATOR;
+        let count = self.royalty_creator_count as usize;
+        self.royalty_creators[..count]
             .iter()
-            .position(|b| !Self::is_empty_bid(b) && b.bidder == *bidder)
+            .zip(&self.royalty_shares[..count])
+            .map(|(creator, share)| (*creator, (royalty * *share as u128 / 100) as u64))
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
     }
 
-    /// Check if bidder has an existing bid
-    pub fn has_bid(&self, bidder: &Pubkey) -> bool {
-        self.find_bid_index(bidder).is_some()
+    /// Check if the sale pays the verified creators of the token their royalties
+    pub fn pays_royalties(&self) -> bool {
+        self.royalties == 1
     }
 
-    /// Get the top bid (highest amount, lowest insertion_index for ties)
-    pub fn get_top_bid(&self) -> Option<&Bid> {
-        self.bids
-            .iter()
-            .filter(|b| !Self::is_empty_bid(b))
-            .max_by(|a, b| {
-                // First compare by lamports (higher is better)
-                match a.lamports.cmp(&b.lamports) {
-                    std::cmp::Ordering::Equal => {
-                        // If equal, lower insertion_index wins (earlier bid)
-                        b.insertion_index.cmp(&a.insertion_index)
-                    }
-                    other => other,
-                }
-            })
-    }
-
-    /// Add or update a bid
-    /// Returns Ok(true) if max bidders reached after this operation, Ok(false) otherwise
-    pub fn upsert_bid(&mut self, bidder: Pubkey, lamports: u64) -> Result<bool> {
-        if let Some(idx) = self.find_bid_index(&bidder) {
-            // Update existing bid - assign highest insertion index
-            let old_insertion_index = self.bids[idx].insertion_index;
-            let new_insertion_index = self.next_insertion_index;
-
-            self.bids[idx].lamports = lamports;
-            self.bids[idx].insertion_index = new_insertion_index;
-
-            // Decrement insertion indices higher than old value
-            for bid in self.bids.iter_mut() {
-                if !Self::is_empty_bid(bid) && bid.insertion_index > old_insertion_index {
-                    bid.insertion_index -= 1;
-                }
+    /// Check if bids are escrowed in an SPL token rather than lamports
+    pub fn pays_in_tokens(&self) -> bool {
+        self.payment_mint != Pubkey::default()
+    }
+
+    /// Ensure the auction is of the given kind
+    pub fn require_kind(&self, kind: u8) -> Result<()> {
+        require!(self.kind == kind, BiddingError::InvalidAuctionKind);
+        Ok(())
+    }
+
+    /// Current Dutch auction price at the given unix timestamp
+    /// The price reaches the floor one step (one second for linear decay) before end_ts,
+    /// so the floor stays on offer for the final step of the bidding window
+    pub fn dutch_price(&self, now: i64) -> u64 {
+        if now <= self.start_ts {
+            return self.dutch_start_price;
+        }
+
+        let step_secs = self.dutch_step_secs.max(1);
+        let decay_secs = self.end_ts - self.start_ts - step_secs;
+        let mut elapsed = now - self.start_ts;
+        if elapsed >= decay_secs {
+            return self.dutch_floor_price;
+        }
+
+        elapsed -= elapsed % step_secs;
+        let price_range = self.dutch_start_price - self.dutch_floor_price;
+        let decay = (price_range as u128 * elapsed as u128 / decay_secs as u128) as u64;
+        self.dutch_start_price - decay
+    }
+
+    /// Store the reserve price in its zero-copy representation
+    fn set_reserve(&mut self, reserve: &ReservePrice) {
+        self.reserve_lamports = 0;
+        self.reserve_commitment = [0u8; 32];
+        match reserve {
+            ReservePrice::None => self.reserve_kind = RESERVE_NONE,
+            ReservePrice::Public { lamports } => {
+                self.reserve_kind = RESERVE_PUBLIC;
+                self.reserve_lamports = *lamports;
             }
+            ReservePrice::Hidden { commitment } => {
+                self.reserve_kind = RESERVE_HIDDEN;
+                self.reserve_commitment = *commitment;
+            }
+        }
+    }
+
+    /// Resolve the reserve price in lamports at the given unix timestamp, verifying the reveal for
+    /// hidden reserves
+    /// A hidden reserve must be revealed within the grace period, after it no bid meets it
+    pub fn resolve_reserve(&self, reveal: Option<&ReserveReveal>, now: i64) -> Result<u64> {
+        match self.reserve_kind {
+            RESERVE_PUBLIC => Ok(self.reserve_lamports),
+            RESERVE_HIDDEN if self.grace_elapsed(now) => {
+                msg!("Hidden reserve not revealed within the grace period");
+                Ok(u64::MAX)
+            }
+            RESERVE_HIDDEN => {
+                let reveal = reveal.ok_or(BiddingError::InvalidReserveReveal)?;
+                require!(
+                    reveal.commitment() == self.reserve_commitment,
+                    BiddingError::InvalidReserveReveal
+                );
+                Ok(reveal.lamports)
+            }
+            _ => Ok(0),
+        }
+    }
 
-            self.next_insertion_index += 1;
-            Ok(false)
+    /// Ensure bids can be placed or cancelled at the given unix timestamp
+    pub fn require_bidding_open(&self, now: i64) -> Result<()> {
+        self.require_pending()?;
+        require!(now >= self.start_ts, BiddingError::AuctionNotStarted);
+        require!(now < self.end_ts, BiddingError::AuctionEnded);
+        Ok(())
+    }
+
+    /// Check if the bidding window has closed at the given unix timestamp
+    pub fn has_ended(&self, now: i64) -> bool {
+        now >= self.end_ts
+    }
+
+    /// Ensure sealed bids can be revealed at the given unix timestamp
+    pub fn require_reveal_open(&self, now: i64) -> Result<()> {
+        self.require_pending()?;
+        require!(
+            now >= self.end_ts && now < self.reveal_end_ts,
+            BiddingError::RevealNotOpen
+        );
+        Ok(())
+    }
+
+    /// Unix timestamp from which the auction can be settled
+    /// (the end of the bidding window, or of the reveal phase for sealed auctions)
+    fn settle_ts(&self) -> i64 {
+        if self.kind == AUCTION_KIND_SEALED {
+            self.reveal_end_ts
         } else {
-            // Add new bid
-            require!(
-                (self.bid_count as usize) < MAX_BIDS,
-                crate::error::BiddingError::MaxBidsReached
-            );
+            self.end_ts
+        }
+    }
+
+    /// Check if the auction can be settled at the given unix timestamp
+    pub fn can_settle(&self, now: i64) -> bool {
+        now >= self.settle_ts()
+    }
 
-            // Find first empty slot
-            let empty_idx = self
-                .bids
-                .iter()
-                .position(Self::is_empty_bid)
-                .ok_or(crate::error::BiddingError::MaxBidsReached)?;
-
-            self.bids[empty_idx] = Bid {
-                bidder,
-                lamports,
-                insertion_index: self.next_insertion_index,
-                padding: [0u8; 7],
-            };
-            self.bid_count += 1;
-            self.next_insertion_index += 1;
-            Ok(self.bid_count as usize == MAX_BIDS)
-        }
-    }
-
-    /// Remove a bid
-    pub fn remove_bid(&mut self, bidder: &Pubkey) -> Result<()> {
-        let idx = self
-            .find_bid_index(bidder)
-            .ok_or(crate::error::BiddingError::NoBidFound)?;
-
-        let removed_insertion_index = self.bids[idx].insertion_index;
-        self.bids[idx] = Bid::default();
-        self.bid_count -= 1;
-
-        // Decrement insertion indices higher than the removed bid
-        for bid in self.bids.iter_mut() {
-            if !Self::is_empty_bid(bid) && bid.insertion_index > removed_insertion_index {
-                bid.insertion_index -= 1;
+    /// Check if the grace period for settling the auction has elapsed at the given unix timestamp
+    pub fn grace_elapsed(&self, now: i64) -> bool {
+        now >= self.settle_ts().saturating_add(self.grace_period_secs)
+    }
+
+    /// Ensure the auction has not been settled yet
+    pub fn require_pending(&self) -> Result<()> {
+        require!(
+            self.outcome == AUCTION_OUTCOME_PENDING,
+            BiddingError::AuctionConcluded
+        );
+        Ok(())
+    }
+
+    /// Ensure the auction has been settled, so its bids can be claimed
+    pub fn require_concluded(&self) -> Result<()> {
+        require!(
+            self.outcome != AUCTION_OUTCOME_PENDING,
+            BiddingError::AuctionNotConcluded
+        );
+        Ok(())
+    }
+
+    /// Check if the given bidder won the settled auction
+    pub fn is_winner(&self, bidder: &Pubkey) -> bool {
+        self.outcome == AUCTION_OUTCOME_BID_WON && *bidder == self.top_bidder
+    }
+
+    /// Check if a bid's deposit is forfeited to the owner at settlement
+    pub fn is_forfeited(&self, bid: &Bid) -> bool {
+        self.kind == AUCTION_KIND_SEALED && self.forfeit_unrevealed == 1 && !bid.revealed
+    }
+
+    /// Soft close: push end_ts back if a raised bid lands inside the extension window
+    /// Returns true if the end time was extended
+    pub fn extend_for_late_bid(&mut self, now: i64) -> bool {
+        if self.extension_window_secs == 0 || now < self.end_ts - self.extension_window_secs {
+            return false;
+        }
+
+        let extended_end_ts = self
+            .end_ts
+            .saturating_add(self.extension_secs)
+            .min(self.max_end_ts);
+        if extended_end_ts <= self.end_ts {
+            return false;
+        }
+
+        self.end_ts = extended_end_ts;
+        true
+    }
+
+    /// Smallest raise over the given amount allowed by the increment rules
+    fn increment_over(&self, amount: u64) -> u64 {
+        let bps_step = (amount as u128 * self.min_increment_bps as u128).div_ceil(BPS_DENOMINATOR);
+        // Both rules apply, and a bid must always strictly beat the top
+        (bps_step as u64).max(self.min_increment_lamports).max(1)
+    }
+
+    /// Check if the auction has a leading bid
+    pub fn has_top_bid(&self) -> bool {
+        self.top_bidder != Pubkey::default()
+    }
+
+    /// Minimum amount a new or raised bid must reach to beat the top bid, at least one lamport
+    /// above it when no increment rules are configured
+    /// Returns None if there are no bids yet
+    pub fn min_next_bid(&self) -> Option<u64> {
+        if !self.has_top_bid() {
+            return None;
+        }
+
+        Some(
+            self.top_lamports
+                .saturating_add(self.increment_over(self.top_lamports)),
+        )
+    }
+
+    /// Refresh the aggregates from the leading bid of the ranking (None once no bid is ranked)
+    /// A proxy leader is raised only as far as needed to beat the runner-up
+    pub fn set_top(&mut self, top: Option<&Bid>) {
+        match top {
+            Some(bid) => {
+                self.top_bidder = bid.bidder;
+                self.top_ceiling = bid.escrow();
+                self.second_lamports = bid.next_rank;
+                self.top_lamports = if bid.is_proxy() {
+                    self.proxy_price(bid.max_lamports)
+                } else {
+                    bid.lamports
+                };
+            }
+            None => {
+                self.top_bidder = Pubkey::default();
+                self.top_ceiling = 0;
+                self.second_lamports = 0;
+                self.top_lamports = 0;
             }
         }
+    }
 
-        // Decrement next_insertion_index since we removed a bid
-        self.next_insertion_index -= 1;
+    /// Lowest price for a proxy bid with the given ceiling to stay on top: the runner-up plus
+    /// the increment (at least the public reserve), capped at the ceiling
+    fn proxy_price(&self, ceiling: u64) -> u64 {
+        let needed = if self.second_lamports == 0 {
+            1
+        } else {
+            self.second_lamports
+                .saturating_add(self.increment_over(self.second_lamports))
+        };
+        needed.max(self.reserve_lamports).min(ceiling)
+    }
 
-        Ok(())
+    /// Raise a proxy leader to the reserve resolved at conclusion, within its ceiling
+    /// A hidden reserve is unknown while bidding, so the proxy price could not account for it
+    pub fn raise_top_to_reserve(&mut self, reserve: u64) {
+        self.top_lamports = self.top_lamports.max(reserve).min(self.top_ceiling);
+    }
+
+    /// Price the winner pays: the leading bid for first-price auctions, otherwise the
+    /// runner-up (or the reserve if there is none), never below the reserve nor above the top bid
+    pub fn clearing_price(&self, reserve: u64) -> u64 {
+        if self.settlement_mode != SETTLEMENT_SECOND_PRICE {
+            return self.top_lamports;
+        }
+
+        self.second_lamports.max(reserve).min(self.top_lamports)
+    }
+
+    /// Keeper bounty owed out of the given sale price
+    pub fn keeper_bounty(&self, price: u64) -> u64 {
+        (price as u128 * self.keeper_bounty_bps as u128 / BPS_DENOMINATOR) as u64
     }
 }
 
-/// Individual bid information
-#[zero_copy]
-#[repr(C)]
-#[derive(Default)]
+/// Per-mint auction sequence in PDA ["auction_counter", token_mint]
+/// Auctions of the mint live at ["auction", token_mint, nonce] for every nonce below `count`,
+/// so past auctions stay enumerable after they close, through the AuctionRecord of each settled one
+#[account]
+#[derive(Default, InitSpace)]
+pub struct AuctionCounter {
+    /// Mint whose auctions are counted
+    pub token_mint: Pubkey,
+    /// Number of auctions created for the mint, and the nonce of the next one
+    pub count: u64,
+    /// The mint was created by create_auction with Metaplex metadata, so every later auction
+    /// of it is a resale paying creator royalties
+    pub pays_royalties: bool,
+    pub bump: u8,
+}
+
+impl AuctionCounter {
+    /// Take the nonce for a new auction of the mint
+    pub fn next_nonce(&mut self, token_mint: Pubkey, bump: u8) -> u64 {
+        self.token_mint = token_mint;
+        self.bump = bump;
+        let nonce = self.count;
+        self.count += 1;
+        nonce
+    }
+}
+
+/// Individual bid, escrowing the bidder's lamports in PDA ["bid", auction, bidder]
+/// The account balance is its rent plus `escrow()`, or just its rent when the auction is paid in an
+/// SPL token and the escrow sits in the bid PDA's associated token account
+/// Amounts are in lamports, or in base units of the auction's payment mint
+#[account]
+#[derive(Default, InitSpace)]
 pub struct Bid {
-    pub bidder: Pubkey,      // 32 bytes
-    pub lamports: u64,       // 8 bytes
-    pub insertion_index: u8, // 1 byte - order of bid placement
-    pub padding: [u8; 7],    // 7 bytes - for 64-bit alignment
+    /// Auction this bid belongs to
+    pub auction: Pubkey,
+    /// Bidder, who receives the escrow and rent back when the bid is closed
+    pub bidder: Pubkey,
+    /// Plain bid amount (sealed: deposit until revealed, then the revealed amount)
+    pub lamports: u64,
+    /// Proxy bid ceiling, escrowed in full (0 for plain bids)
+    pub max_lamports: u64,
+    /// Sealed: the bid amount has been revealed
+    pub revealed: bool,
+    /// Sealed: hash(amount || salt)
+    pub commitment: [u8; 32],
+    /// Bidder of the next lower bid in the auction's ranking, default for the lowest bid
+    pub next_bidder: Pubkey,
+    /// Escrow of the next lower bid in the ranking, 0 for the lowest bid
+    pub next_rank: u64,
+    pub bump: u8,
+}
+
+impl Bid {
+    /// Check if this is a proxy bid that the program raises automatically
+    pub fn is_proxy(&self) -> bool {
+        self.max_lamports > 0
+    }
+
+    /// Lamports held in escrow for this bid (the ceiling for proxy bids)
+    pub fn escrow(&self) -> u64 {
+        self.lamports.max(self.max_lamports)
+    }
+}
+
+/// Provenance record of a settled auction in PDA ["auction_record", auction], kept after the
+/// auction account closes
+#[account]
+#[derive(Default, InitSpace)]
+pub struct AuctionRecord {
+    /// Settled auction, closed once every bid is settled
+    pub auction: Pubkey,
+    /// Auctioned mint
+    pub token_mint: Pubkey,
+    /// Sequence number of the auction among the mint's auctions
+    pub nonce: u64,
+    /// Amount of the mint auctioned
+    pub token_amount: u64,
+    /// Seller of the token
+    pub seller: Pubkey,
+    /// Buyer of the token, default if no bid won
+    pub winner: Pubkey,
+    /// Price the winner paid, in lamports or base units of `payment_mint` (0 if no bid won)
+    pub price: u64,
+    /// SPL token the price was paid in, default for lamports
+    pub payment_mint: Pubkey,
+    /// Unix timestamp the outcome was recorded at
+    pub settled_ts: i64,
+    pub bump: u8,
+}
+
+impl AuctionRecord {
+    /// Record the outcome of an auction, `winner` is default if no bid won
+    pub fn record(
+        &mut self,
+        auction_key: Pubkey,
+        auction: &Auction,
+        winner: Pubkey,
+        price: u64,
+        settled_ts: i64,
+        bump: u8,
+    ) {
+        self.auction = auction_key;
+        self.token_mint = auction.token_mint;
+        self.nonce = auction.nonce;
+        self.token_amount = auction.token_amount;
+        self.seller = auction.owner;
+        self.winner = winner;
+        self.price = price;
+        self.payment_mint = auction.payment_mint;
+        self.settled_ts = settled_ts;
+        self.bump = bump;
+    }
 }
@@ -0,0 +1,154 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, Bid};
+use crate::utils::{
+    payment_account, transfer_lamports, BidRanking, TokenEscrow, AUCTION_KIND_ENGLISH,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, System};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Accounts of an open bid (`place_bid` or `place_proxy_bid`)
+pub struct BidPlacement<'a, 'info> {
+    pub auction: &'a AccountLoader<'info, Auction>,
+    pub bid: &'a mut Account<'info, Bid>,
+    pub bid_bump: u8,
+    pub bidder: &'a Signer<'info>,
+    pub payment_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    pub bid_escrow: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub bidder_payment_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub payment_token_program: Option<&'a Interface<'info, TokenInterface>>,
+    pub system_program: &'a Program<'info, System>,
+    /// Neighbouring bids in the ranking, followed by the payment mint's transfer hook accounts
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+}
+
+/// Set an open bid to `lamports` and `max_lamports` (a proxy ceiling), moving the difference
+/// between its old and new escrow in or out of the bid PDA and re-ranking it
+/// Late bids that raise the escrow or take the lead extend the end time (soft close)
+pub fn escrow_and_rank(accounts: BidPlacement, lamports: u64, max_lamports: u64) -> Result<()> {
+    let escrow_amount = lamports.max(max_lamports);
+    require!(escrow_amount > 0, BiddingError::InvalidBidAmount);
+
+    let bidder = accounts.bidder.key();
+    let now = Clock::get()?.unix_timestamp;
+    let mut auction = accounts.auction.load_mut()?;
+
+    // Only English auctions take open bids
+    auction.require_kind(AUCTION_KIND_ENGLISH)?;
+
+    // Bids are only accepted inside the auction's time window
+    auction.require_bidding_open(now)?;
+
+    // Auctions paid in an SPL token escrow bids in the bid PDA's token account instead
+    let token_payment = if auction.pays_in_tokens() {
+        Some((
+            TokenEscrow::from_accounts(
+                accounts.payment_token_program,
+                accounts.payment_mint,
+                accounts.bid_escrow,
+                accounts.remaining_accounts,
+            )?,
+            payment_account(accounts.bidder_payment_account)?,
+        ))
+    } else {
+        None
+    };
+
+    // A plain bid replaces any proxy bid (and the other way round), so compare full escrows
+    let bid = accounts.bid;
+    let is_new_bid = bid.bidder == Pubkey::default();
+    let existing_escrow = bid.escrow();
+
+    // Make sure that there's an actual change
+    require!(escrow_amount != existing_escrow, BiddingError::NoBidChange);
+
+    if escrow_amount > existing_escrow {
+        // New or raised bids (or ceilings) must beat the current top bid by the configured increment
+        if let Some(min_bid) = auction.min_next_bid() {
+            require!(escrow_amount >= min_bid, BiddingError::BidIncrementTooLow);
+        }
+
+        // Increasing bid - escrow the additional amount from the bidder
+        let additional_lamports = escrow_amount - existing_escrow;
+
+        if let Some((escrow, bidder_tokens)) = &token_payment {
+            // The bidder also pays any transfer fee, so the escrow covers the full bid
+            escrow.deposit(
+                bidder_tokens,
+                accounts.bidder.to_account_info(),
+                additional_lamports,
+            )?;
+        } else {
+            require!(
+                accounts.bidder.lamports() >= additional_lamports,
+                BiddingError::InsufficientFunds
+            );
+
+            system_program::transfer(
+                CpiContext::new(
+                    accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: accounts.bidder.to_account_info(),
+                        to: bid.to_account_info(),
+                    },
+                ),
+                additional_lamports,
+            )?;
+        }
+    } else {
+        // Refund the excess escrow back to the bidder
+        if let Some((escrow, bidder_tokens)) = &token_payment {
+            escrow.withdraw(
+                bid,
+                bidder_tokens.to_account_info(),
+                existing_escrow - escrow_amount,
+            )?;
+        } else {
+            transfer_lamports(
+                &bid.to_account_info(),
+                &accounts.bidder.to_account_info(),
+                existing_escrow - escrow_amount,
+            )?;
+        }
+    }
+
+    // Re-rank the bid at its new escrow, relinking the neighbouring bids passed in the
+    // remaining accounts
+    let previous_top_bidder = auction.top_bidder;
+    let mut ranking = BidRanking::from_accounts(
+        accounts.auction.key(),
+        &auction,
+        bid.key(),
+        accounts.remaining_accounts,
+    )?;
+    if is_new_bid {
+        bid.auction = accounts.auction.key();
+        bid.bidder = bidder;
+        bid.bump = accounts.bid_bump;
+        auction.bid_count += 1;
+    } else {
+        ranking.remove(&mut auction, bid)?;
+    }
+    bid.lamports = lamports;
+    bid.max_lamports = max_lamports;
+
+    ranking.insert(&mut auction, bid)?;
+    ranking.exit()?;
+
+    // Soft close - late bids that raise the escrow or take the lead push the end time back
+    // to counter sniping
+    let raises_stakes =
+        escrow_amount > existing_escrow || auction.top_bidder != previous_top_bidder;
+    if raises_stakes && auction.extend_for_late_bid(now) {
+        msg!("Late bid, auction extended until {}", auction.end_ts);
+    }
+
+    msg!("Total bids: {}", auction.bid_count);
+    msg!(
+        "Current top bid: {} lamports by {}",
+        auction.top_lamports,
+        auction.top_bidder
+    );
+
+    Ok(())
+}
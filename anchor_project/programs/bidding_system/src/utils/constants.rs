@@ -1,2 +1,37 @@
-/// Maximum number of bids per item
-pub const MAX_BIDS: usize = 10;
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Largest keeper bounty an auction may offer (basis points of the sale price)
+pub const MAX_KEEPER_BOUNTY_BPS: u16 = 500;
+
+/// Shortest grace period an auction may give its owner (or a keeper) to settle it
+pub const MIN_GRACE_PERIOD_SECS: i64 = 60;
+
+/// Most creators a minted token's metadata can list (Metaplex limit)
+pub const MAX_CREATORS: usize = 5;
+
+/// Auction has no reserve price
+pub const RESERVE_NONE: u8 = 0;
+/// Auction reserve price is stored in plain lamports
+pub const RESERVE_PUBLIC: u8 = 1;
+/// Auction reserve price is stored as a hash commitment
+pub const RESERVE_HIDDEN: u8 = 2;
+
+/// Ascending-bid auction
+pub const AUCTION_KIND_ENGLISH: u8 = 0;
+/// Descending-price auction
+pub const AUCTION_KIND_DUTCH: u8 = 1;
+/// Sealed-bid commit-reveal auction
+pub const AUCTION_KIND_SEALED: u8 = 2;
+
+/// Winner pays their own bid
+pub const SETTLEMENT_FIRST_PRICE: u8 = 0;
+/// Winner pays the second-highest bid (Vickrey)
+pub const SETTLEMENT_SECOND_PRICE: u8 = 1;
+
+/// Auction has not been settled yet
+pub const AUCTION_OUTCOME_PENDING: u8 = 0;
+/// Top bid won: the winner claims the token, every other bid claims a refund
+pub const AUCTION_OUTCOME_BID_WON: u8 = 1;
+/// No bid won (reserve not met, cancelled or bought outright): every bid claims a refund
+pub const AUCTION_OUTCOME_REFUND_ALL: u8 = 2;
@@ -0,0 +1,192 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, Bid};
+use crate::utils::TokenMint;
+use anchor_lang::prelude::*;
+use anchor_spl::token::accessor;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Move escrowed lamports out of a program-owned account (the auction PDA)
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Bid escrow held in the bid PDA's associated token account, for auctions paid in an SPL token
+pub struct TokenEscrow<'a, 'info> {
+    pub payment: TokenMint<'a, 'info>,
+    /// Token account owned by the bid PDA (the token program checks the owner on every move)
+    pub escrow: AccountInfo<'info>,
+}
+
+impl<'a, 'info> TokenEscrow<'a, 'info> {
+    /// Collect the optional payment accounts of a handler, required for auctions paid in an SPL token
+    pub fn from_accounts(
+        token_program: Option<&Interface<'info, TokenInterface>>,
+        payment_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        escrow: Option<&InterfaceAccount<'info, TokenAccount>>,
+        hook_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self> {
+        match (token_program, payment_mint, escrow) {
+            (Some(token_program), Some(payment_mint), Some(escrow)) => Ok(Self {
+                payment: TokenMint::new(token_program, payment_mint, hook_accounts),
+                escrow: escrow.to_account_info(),
+            }),
+            _ => err!(BiddingError::PaymentAccountsMissing),
+        }
+    }
+
+    /// Move tokens from a payment account into the escrow, signed by its owner
+    /// The sender pays any transfer fee on top, so exactly `amount` arrives in the escrow
+    /// Returns the amount sent
+    pub fn deposit(
+        &self,
+        from: &InterfaceAccount<'info, TokenAccount>,
+        authority: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<u64> {
+        let gross = self.payment.gross_amount(amount)?;
+        require!(from.amount >= gross, BiddingError::InsufficientFunds);
+
+        self.payment.transfer(
+            from.to_account_info(),
+            self.escrow.clone(),
+            authority,
+            gross,
+            &[],
+        )?;
+        Ok(gross)
+    }
+
+    /// Move tokens out of the escrow, signed by the bid PDA which owns it
+    /// Any transfer fee is withheld from what the recipient receives
+    pub fn withdraw(
+        &self,
+        bid: &Account<'info, Bid>,
+        to: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        self.payment.transfer(
+            self.escrow.clone(),
+            to,
+            bid.to_account_info(),
+            amount,
+            &[&bid_seeds(bid)],
+        )
+    }
+
+    /// Move everything left in the escrow to `to` and close it, returning its rent to the bidder
+    /// Returns the amount moved
+    pub fn drain(
+        &self,
+        bid: &Account<'info, Bid>,
+        to: AccountInfo<'info>,
+        bidder: AccountInfo<'info>,
+    ) -> Result<u64> {
+        let amount = accessor::amount(&self.escrow)?;
+        if amount > 0 {
+            self.withdraw(bid, to, amount)?;
+        }
+
+        self.payment.close_account(
+            self.escrow.clone(),
+            bidder,
+            bid.to_account_info(),
+            &[&bid_seeds(bid)],
+        )?;
+        Ok(amount)
+    }
+}
+
+/// Signer seeds of a bid PDA
+fn bid_seeds<'b>(bid: &'b Account<Bid>) -> [&'b [u8]; 4] {
+    [
+        b"bid",
+        bid.auction.as_ref(),
+        bid.bidder.as_ref(),
+        std::slice::from_ref(&bid.bump),
+    ]
+}
+
+/// Unwrap an optional payment token account, required for auctions paid in an SPL token
+pub fn payment_account<'a, 'info>(
+    account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+) -> Result<&'a InterfaceAccount<'info, TokenAccount>> {
+    account.ok_or_else(|| error!(BiddingError::PaymentAccountsMissing))
+}
+
+/// Refund a losing bid of a settled auction by closing its bid account to the bidder
+/// Unrevealed sealed deposits are forfeited to the owner when configured
+/// Auctions paid in an SPL token also drain the bid's token escrow to the bidder's payment account
+pub fn refund_bid<'info>(
+    auction: &mut Auction,
+    bid: &Account<'info, Bid>,
+    bidder: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    token_refund: Option<(TokenEscrow<'_, 'info>, AccountInfo<'info>)>,
+) -> Result<()> {
+    require!(
+        !auction.is_winner(&bid.bidder),
+        BiddingError::WinningBidNotRefundable
+    );
+
+    if let Some((escrow, bidder_tokens)) = token_refund {
+        let amount = escrow.drain(bid, bidder_tokens, bidder.clone())?;
+        msg!("Refunded {} payment tokens to {}", amount, bid.bidder);
+    }
+
+    if auction.is_forfeited(bid) {
+        transfer_lamports(&bid.to_account_info(), owner, bid.escrow())?;
+        msg!("Forfeited {} lamports from {}", bid.escrow(), bid.bidder);
+    }
+
+    let refund = bid.to_account_info().lamports();
+    bid.close(bidder.clone())?;
+    auction.bid_count -= 1;
+
+    msg!("Refunded {} lamports to {}", refund, bid.bidder);
+    Ok(())
+}
+
+/// Transfer every token out of the vault and close the vault, returning its rent to the owner
+/// Both are signed by the auction PDA, which owns the vault (the auction must not be borrowed)
+pub fn release_vault<'info>(
+    asset: &TokenMint<'_, 'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    owner: AccountInfo<'info>,
+    auction: &AccountLoader<'info, Auction>,
+    auction_bump: u8,
+) -> Result<()> {
+    let nonce = auction.load()?.nonce.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"auction", vault.mint.as_ref(), &nonce, &[auction_bump]]];
+    let auction = auction.to_account_info();
+    asset.transfer(
+        vault.to_account_info(),
+        to,
+        auction.clone(),
+        vault.amount,
+        signer_seeds,
+    )?;
+    asset.close_account(vault.to_account_info(), owner, auction, signer_seeds)
+}
+
+/// Burn every token held in the vault and close the vault, returning its rent to the owner
+pub fn burn_vault<'info>(
+    asset: &TokenMint<'_, 'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    owner: AccountInfo<'info>,
+    auction: &AccountLoader<'info, Auction>,
+    auction_bump: u8,
+) -> Result<()> {
+    let nonce = auction.load()?.nonce.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"auction", vault.mint.as_ref(), &nonce, &[auction_bump]]];
+    let auction = auction.to_account_info();
+    asset.burn(
+        vault.to_account_info(),
+        auction.clone(),
+        vault.amount,
+        signer_seeds,
+    )?;
+    asset.close_account(vault.to_account_info(), owner, auction, signer_seeds)
+}
@@ -1,3 +1,13 @@
+pub mod bidding;
 pub mod constants;
+pub mod escrow;
+pub mod ranking;
+pub mod royalties;
+pub mod token;
 
+pub use bidding::*;
 pub use constants::*;
+pub use escrow::*;
+pub use ranking::*;
+pub use royalties::*;
+pub use token::*;
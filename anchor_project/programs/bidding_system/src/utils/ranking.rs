@@ -0,0 +1,143 @@
+use crate::error::BiddingError;
+use crate::state::{Auction, Bid};
+use crate::utils::AUCTION_KIND_SEALED;
+use anchor_lang::prelude::*;
+
+/// Ranking of an auction's bids: a singly linked list ordered by escrow, highest first (ties keep
+/// the earlier bid first), headed by `Auction::top_bidder`
+/// Each bid caches the rank of the bid after it, so the runner-up of a leader that lowers or
+/// withdraws its bid is known without scanning; the neighbouring bid accounts touched by an update
+/// are passed in the remaining accounts
+pub struct BidRanking<'info> {
+    kind: u8,
+    /// Neighbouring bids with the link they were loaded with, to write back only the changed ones
+    neighbours: Vec<(AccountInfo<'info>, Bid, (Pubkey, u64))>,
+}
+
+impl<'info> BidRanking<'info> {
+    /// Collect the bids of `auction` among the remaining accounts, other accounts (transfer hook
+    /// accounts, the updated bid itself) are skipped
+    pub fn from_accounts(
+        auction_key: Pubkey,
+        auction: &Auction,
+        bid_key: Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<Self> {
+        let mut neighbours: Vec<(AccountInfo<'info>, Bid, (Pubkey, u64))> = Vec::new();
+        for account in accounts {
+            if account.owner != &crate::ID
+                || account.key() == bid_key
+                || neighbours.iter().any(|(info, ..)| info.key == account.key)
+            {
+                continue;
+            }
+            let Ok(bid) = Bid::try_deserialize(&mut &account.try_borrow_data()?[..]) else {
+                continue;
+            };
+            if bid.auction == auction_key {
+                let link = (bid.next_bidder, bid.next_rank);
+                neighbours.push((account.clone(), bid, link));
+            }
+        }
+
+        Ok(Self {
+            kind: auction.kind,
+            neighbours,
+        })
+    }
+
+    /// Check if a bid takes part in the ranking (sealed bids only once revealed)
+    fn is_ranked(kind: u8, bid: &Bid) -> bool {
+        kind != AUCTION_KIND_SEALED || bid.revealed
+    }
+
+    /// Neighbouring bid of the given bidder
+    fn bid_of(&mut self, bidder: &Pubkey) -> Result<&mut Bid> {
+        self.neighbours
+            .iter_mut()
+            .map(|(_, bid, _)| bid)
+            .find(|bid| bid.bidder == *bidder)
+            .ok_or_else(|| error!(BiddingError::RankingAccountsMissing))
+    }
+
+    /// Neighbouring bid ranked right above the given bidder
+    fn bid_before(&mut self, bidder: &Pubkey) -> Result<&mut Bid> {
+        self.neighbours
+            .iter_mut()
+            .map(|(_, bid, _)| bid)
+            .find(|bid| bid.next_bidder == *bidder)
+            .ok_or_else(|| error!(BiddingError::RankingAccountsMissing))
+    }
+
+    /// Take a bid out of the ranking before it is changed or closed
+    /// Needs the bid ranked right above it, or the next one if it leads
+    pub fn remove(&mut self, auction: &mut Auction, bid: &mut Bid) -> Result<()> {
+        if !Self::is_ranked(self.kind, bid) {
+            return Ok(());
+        }
+
+        if auction.top_bidder == bid.bidder {
+            if bid.next_bidder == Pubkey::default() {
+                auction.set_top(None);
+            } else {
+                let next_bidder = bid.next_bidder;
+                auction.set_top(Some(self.bid_of(&next_bidder)?));
+            }
+        } else {
+            let previous = self.bid_before(&bid.bidder)?;
+            previous.next_bidder = bid.next_bidder;
+            previous.next_rank = bid.next_rank;
+            if previous.bidder == auction.top_bidder {
+                auction.set_top(Some(previous));
+            }
+        }
+
+        bid.next_bidder = Pubkey::default();
+        bid.next_rank = 0;
+        Ok(())
+    }
+
+    /// Put a new, changed or revealed bid back into the ranking at its escrow
+    /// Needs the lowest bid ranked at or above it, unless it takes the lead
+    pub fn insert(&mut self, auction: &mut Auction, bid: &mut Bid) -> Result<()> {
+        let rank = bid.escrow();
+
+        if !auction.has_top_bid() || rank > auction.top_ceiling {
+            bid.next_bidder = auction.top_bidder;
+            bid.next_rank = auction.top_ceiling;
+            auction.set_top(Some(bid));
+            return Ok(());
+        }
+
+        let kind = self.kind;
+        let previous = self
+            .neighbours
+            .iter_mut()
+            .map(|(_, neighbour, _)| neighbour)
+            .find(|neighbour| {
+                Self::is_ranked(kind, neighbour)
+                    && neighbour.escrow() >= rank
+                    && rank > neighbour.next_rank
+            })
+            .ok_or_else(|| error!(BiddingError::RankingAccountsMissing))?;
+
+        bid.next_bidder = previous.next_bidder;
+        bid.next_rank = previous.next_rank;
+        previous.next_bidder = bid.bidder;
+        previous.next_rank = rank;
+        if previous.bidder == auction.top_bidder {
+            auction.set_top(Some(previous));
+        }
+        Ok(())
+    }
+
+    /// Write the relinked neighbouring bids back to their accounts
+    pub fn exit(&self) -> Result<()> {
+        for (info, bid, link) in &self.neighbours {
+            if (bid.next_bidder, bid.next_rank) != *link {
+                bid.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            }
+        }
+        Ok(())
+    }
+}
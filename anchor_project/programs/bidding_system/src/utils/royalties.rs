@@ -0,0 +1,50 @@
+use crate::error::BiddingError;
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::{self, accounts::Metadata};
+
+/// Royalty rate and verified creators of a token with their shares, read from its Metaplex
+/// metadata account (the share of unverified creators stays with the seller)
+pub fn verified_creators(
+    metadata: &AccountInfo,
+    token_mint: &Pubkey,
+) -> Result<(u16, Vec<(Pubkey, u8)>)> {
+    require_keys_eq!(
+        *metadata.key,
+        Metadata::find_pda(token_mint).0,
+        BiddingError::InvalidMetadataAccount
+    );
+    require_keys_eq!(
+        *metadata.owner,
+        mpl_token_metadata::ID,
+        BiddingError::InvalidMetadataAccount
+    );
+    let metadata = Metadata::from_bytes(&metadata.try_borrow_data()?)
+        .map_err(|_| error!(BiddingError::InvalidMetadataAccount))?;
+
+    let creators = metadata
+        .creators
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|creator| creator.verified)
+        .map(|creator| (creator.address, creator.share))
+        .collect();
+    Ok((metadata.seller_fee_basis_points, creators))
+}
+
+/// Find a royalty recipient among the remaining accounts
+pub fn creator_account<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    key: &Pubkey,
+) -> Result<&'a AccountInfo<'info>> {
+    accounts
+        .iter()
+        .find(|account| account.key == key)
+        .ok_or_else(|| error!(BiddingError::CreatorAccountMissing))
+}
+
+/// Check if a creator's wallet can take a royalty of `amount` lamports: a payout leaving an
+/// unfunded wallet below rent exemption would fail the whole conclusion
+pub fn can_receive_lamports(account: &AccountInfo, amount: u64) -> Result<bool> {
+    let balance = account.lamports().saturating_add(amount);
+    Ok(balance >= Rent::get()?.minimum_balance(account.data_len()))
+}
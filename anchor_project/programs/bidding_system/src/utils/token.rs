@@ -0,0 +1,140 @@
+use crate::error::BiddingError;
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{
+            instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig,
+        },
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    onchain::invoke_transfer_checked,
+};
+use anchor_spl::token_interface::{self, Mint, TokenInterface};
+
+/// A mint of either token program, with the extra accounts its transfer hook needs (if any)
+pub struct TokenMint<'a, 'info> {
+    pub token_program: AccountInfo<'info>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    /// Accounts forwarded from `remaining_accounts`, resolved against the mint's hook on every transfer
+    pub hook_accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> TokenMint<'a, 'info> {
+    pub fn new(
+        token_program: &Interface<'info, TokenInterface>,
+        mint: &'a InterfaceAccount<'info, Mint>,
+        hook_accounts: &'a [AccountInfo<'info>],
+    ) -> Self {
+        Self {
+            token_program: token_program.to_account_info(),
+            mint,
+            hook_accounts,
+        }
+    }
+
+    /// `transfer_checked` between two token accounts of the mint, invoking its transfer hook
+    pub fn transfer(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        invoke_transfer_checked(
+            self.token_program.key,
+            from,
+            self.mint.to_account_info(),
+            to,
+            authority,
+            self.hook_accounts,
+            amount,
+            self.mint.decimals,
+            signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Amount to send so that `net` arrives after the mint's transfer fee (`net` without one)
+    pub fn gross_amount(&self, net: u64) -> Result<u64> {
+        let mint_info = self.mint.to_account_info();
+        let data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        let Ok(fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+            return Ok(net);
+        };
+
+        fee_config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, net)
+            .and_then(|fee| net.checked_add(fee))
+            .ok_or_else(|| error!(BiddingError::InvalidTokenAmount))
+    }
+
+    /// Burn tokens of the mint from `from`
+    pub fn burn(
+        &self,
+        from: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                token_interface::Burn {
+                    mint: self.mint.to_account_info(),
+                    from,
+                    authority,
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Close an emptied token account of the mint, harvesting the transfer fees withheld in it
+    /// to the mint first (Token-2022 refuses to close an account holding withheld fees)
+    pub fn close_account(
+        &self,
+        account: AccountInfo<'info>,
+        destination: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if withheld_fees(&account)? > 0 {
+            let ix = harvest_withheld_tokens_to_mint(
+                self.token_program.key,
+                &self.mint.key(),
+                &[account.key],
+            )?;
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[
+                    self.token_program.clone(),
+                    self.mint.to_account_info(),
+                    account.clone(),
+                ],
+            )?;
+        }
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            token_interface::CloseAccount {
+                account,
+                destination,
+                authority,
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+/// Transfer fees withheld in a token account (always 0 for the legacy token program)
+fn withheld_fees(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(state
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |fees| u64::from(fees.withheld_amount)))
+}
@@ -4,49 +4,201 @@ import { BiddingSystem } from "../target/types/bidding_system";
 import { expect } from "chai";
 import { 
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
   getAssociatedTokenAddress,
   createAssociatedTokenAccountInstruction,
+  createAccount,
+  createAssociatedTokenAccount,
+  createMint,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  getMint,
+  getMintLen,
+  getTokenMetadata,
+  mintTo,
 } from "@solana/spl-token";
-import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Keypair, PublicKey, SystemProgram, Transaction, sendAndConfirmTransaction } from "@solana/web3.js";
+import { createHash } from "crypto";
 
 const MPL_TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 
+// Royalties of minted tokens whose resales pay nobody
+const NO_ROYALTIES = { sellerFeeBasisPoints: 0, creators: [] };
+
+// Bidding window lengths (seconds) - long enough for the bids each test places
+const MAIN_AUCTION_SECS = 180;
+const MAX_BIDS_AUCTION_SECS = 60;
+const SHORT_AUCTION_SECS = 10;
+// Shortest grace period the program accepts (MIN_GRACE_PERIOD_SECS)
+const MIN_GRACE_PERIOD_SECS = 60;
+
+// Auction outcomes recorded at settlement
+const AUCTION_OUTCOME_PENDING = 0;
+const AUCTION_OUTCOME_BID_WON = 1;
+const AUCTION_OUTCOME_REFUND_ALL = 2;
+
+// Size of the AuctionRecord left by each settled auction, its rent is paid by the settling caller
+const AUCTION_RECORD_SIZE = 201;
+
 // Helper function to read zero-copy auction account
 async function readAuction(connection: anchor.web3.Connection, auctionPda: PublicKey) {
   const accountInfo = await connection.getAccountInfo(auctionPda);
   const data = accountInfo.data;
   
   // Parse zero-copy account structure:
-  // discriminator(8) + owner(32) + token_mint(32) + bids(48 * 10 = 480) + bid_count(1) + next_insertion_index(1) + padding(6)
+  // discriminator(8) + owner(32) + token_mint(32) + top_bidder(32) + top_lamports(8) + top_ceiling(8)
+  // + second_lamports(8) + bid_count(4) + padding(4)
+  // + start_ts(8) + end_ts(8) + extension_window_secs(8) + extension_secs(8) + max_end_ts(8)
+  // + reserve_lamports(8) + reserve_commitment(32) + reserve_kind(1) + padding2(7)
+  // + min_increment_lamports(8) + min_increment_bps(2) + keeper_bounty_bps(2) + padding3(4)
+  // + buy_now_lamports(8)
+  // + kind(1) + forfeit_unrevealed(1) + settlement_mode(1) + outcome(1) + minted(1) + royalties(1) + padding4(2)
+  // + dutch_start_price(8) + dutch_floor_price(8) + dutch_step_secs(8) + reveal_end_ts(8)
+  // + sale_lamports(8) + grace_period_secs(8) + token_amount(8) + nonce(8) + payment_mint(32)
+  // + royalty_bps(2) + royalty_creator_count(1) + royalty_shares(5) + royalty_creators(5 * 32)
   const owner = new PublicKey(data.slice(8, 40));
   const tokenMint = new PublicKey(data.slice(40, 72));
-  
-  // Bids start at offset 72
-  const bidsStartOffset = 72;
-  const bidStructSize = 48; // 32 bytes pubkey + 8 bytes lamports + 1 byte insertion_index + 7 bytes padding
-  const maxBids = 10;
-  
-  // bid_count is at offset: 8 + 32 + 32 + (48 * 10) = 552
-  const bidCount = data.readUInt8(552);
-  
-  const bids = [];
-  
-  // Scan all bid slots and collect non-default bids
-  // Bids can be scattered after removals
-  for (let i = 0; i < maxBids; i++) {
-    const offset = bidsStartOffset + (i * bidStructSize);
-    const bidder = new PublicKey(data.slice(offset, offset + 32));
-    
-    // Skip default/empty bids (all zeros)
-    if (!bidder.equals(PublicKey.default)) {
-      const lamports = new anchor.BN(data.readBigUInt64LE(offset + 32));
-      const insertionIndex = data.readUInt8(offset + 40);
-      bids.push({ bidder, lamports, insertionIndex });
+  const topBidder = new PublicKey(data.slice(72, 104));
+  const topLamports = new anchor.BN(data.readBigUInt64LE(104).toString());
+  const topCeiling = new anchor.BN(data.readBigUInt64LE(112).toString());
+  const secondLamports = new anchor.BN(data.readBigUInt64LE(120).toString());
+  const bidCount = data.readUInt32LE(128);
+  const startTs = new anchor.BN(data.readBigInt64LE(136).toString());
+  const endTs = new anchor.BN(data.readBigInt64LE(144).toString());
+  const maxEndTs = new anchor.BN(data.readBigInt64LE(168).toString());
+  const reserveLamports = new anchor.BN(data.readBigUInt64LE(176).toString());
+  const reserveKind = data.readUInt8(216);
+  const minIncrementLamports = new anchor.BN(data.readBigUInt64LE(224).toString());
+  const minIncrementBps = data.readUInt16LE(232);
+  const keeperBountyBps = data.readUInt16LE(234);
+  const buyNowLamports = new anchor.BN(data.readBigUInt64LE(240).toString());
+  const kind = data.readUInt8(248);
+  const forfeitUnrevealed = data.readUInt8(249) === 1;
+  const settlementMode = data.readUInt8(250);
+  const outcome = data.readUInt8(251);
+  const minted = data.readUInt8(252) === 1;
+  const royalties = data.readUInt8(253) === 1;
+  const dutchStartPrice = new anchor.BN(data.readBigUInt64LE(256).toString());
+  const dutchFloorPrice = new anchor.BN(data.readBigUInt64LE(264).toString());
+  const revealEndTs = new anchor.BN(data.readBigInt64LE(280).toString());
+  const saleLamports = new anchor.BN(data.readBigUInt64LE(288).toString());
+  const gracePeriodSecs = new anchor.BN(data.readBigInt64LE(296).toString());
+  const tokenAmount = new anchor.BN(data.readBigUInt64LE(304).toString());
+  const nonce = data.readBigUInt64LE(312);
+  const paymentMint = new PublicKey(data.slice(320, 352));
+  const royaltyBps = data.readUInt16LE(352);
+  const royaltyCreatorCount = data.readUInt8(354);
+  const royaltyShares = Array.from(data.slice(355, 355 + royaltyCreatorCount));
+  const royaltyCreators = Array.from(
+    { length: royaltyCreatorCount },
+    (_, i) => new PublicKey(data.slice(360 + 32 * i, 392 + 32 * i))
+  );
+
+  // Bids live in their own PDAs, owned by the same program
+  const bids = await readBids(connection, accountInfo.owner, auctionPda);
+
+  return {
+    owner,
+    tokenMint,
+    topBidder,
+    topLamports,
+    topCeiling,
+    secondLamports,
+    bidCount,
+    bids,
+    startTs,
+    endTs,
+    maxEndTs,
+    reserveLamports,
+    reserveKind,
+    minIncrementLamports,
+    minIncrementBps,
+    keeperBountyBps,
+    buyNowLamports,
+    kind,
+    forfeitUnrevealed,
+    settlementMode,
+    outcome,
+    minted,
+    royalties,
+    dutchStartPrice,
+    dutchFloorPrice,
+    revealEndTs,
+    saleLamports,
+    gracePeriodSecs,
+    tokenAmount,
+    nonce: Number(nonce),
+    paymentMint,
+    royaltyBps,
+    royaltyShares,
+    royaltyCreators,
+  };
+}
+
+// Helper function to read all bid accounts of an auction
+async function readBids(connection: anchor.web3.Connection, programId: PublicKey, auctionPda: PublicKey) {
+  // discriminator(8) + auction(32) + bidder(32) + lamports(8) + max_lamports(8) + revealed(1)
+  // + commitment(32) + next_bidder(32) + next_rank(8) + bump(1)
+  const bidAccounts = await connection.getProgramAccounts(programId, {
+    filters: [
+      { dataSize: 162 },
+      { memcmp: { offset: 8, bytes: auctionPda.toBase58() } },
+    ],
+  });
+
+  return bidAccounts.map(({ pubkey, account }) => {
+    const data = account.data;
+    return {
+      address: pubkey,
+      bidder: new PublicKey(data.slice(40, 72)),
+      lamports: new anchor.BN(data.readBigUInt64LE(72).toString()),
+      maxLamports: new anchor.BN(data.readBigUInt64LE(80).toString()),
+      revealed: data.readUInt8(88) === 1,
+      nextBidder: new PublicKey(data.slice(121, 153)),
+      nextRank: new anchor.BN(data.readBigUInt64LE(153).toString()),
+    };
+  });
+}
+
+// Helper to find the neighbouring bid accounts that a bid update relinks in the auction's ranking:
+// the bid ranked above the bidder's current bid (the runner-up if it leads) and the lowest other
+// bid ranked at or above its new escrow `rank` (null when the bid is cancelled)
+async function rankingAccounts(
+  program: Program<BiddingSystem>,
+  auctionPda: PublicKey,
+  bidder: PublicKey,
+  rank: anchor.BN | number | null
+) {
+  const { topBidder, bids } = await readAuction(program.provider.connection, auctionPda);
+  const byBidder = new Map(bids.map((bid) => [bid.bidder.toBase58(), bid]));
+
+  // Walk the ranking down from the leader
+  const ranking = [];
+  for (let next = topBidder; !next.equals(PublicKey.default); ) {
+    const bid = byBidder.get(next.toBase58());
+    ranking.push(bid);
+    next = bid.nextBidder;
+  }
+
+  const neighbours = new Set<string>();
+  const index = ranking.findIndex((bid) => bid.bidder.equals(bidder));
+  if (index > 0) {
+    neighbours.add(ranking[index - 1].address.toBase58());
+  } else if (index === 0 && ranking.length > 1) {
+    neighbours.add(ranking[1].address.toBase58());
+  }
+  if (rank !== null) {
+    const above = ranking.filter(
+      (bid, i) => i !== index && anchor.BN.max(bid.lamports, bid.maxLamports).gte(new anchor.BN(rank))
+    );
+    if (above.length > 0) {
+      neighbours.add(above[above.length - 1].address.toBase58());
     }
   }
-  
-  return { owner, tokenMint, bidCount, bids };
+
+  return [...neighbours].map((address) => ({ pubkey: new PublicKey(address), isWritable: true, isSigner: false }));
 }
 
 describe("bidding_system", () => {
@@ -63,6 +215,7 @@ describe("bidding_system", () => {
   let mint: Keypair;
   let auctionPda: PublicKey;
   let ownerTokenAccount: PublicKey;
+  let vault: PublicKey;
   let metadataPda: PublicKey;
 
   before(async () => {
@@ -86,7 +239,7 @@ describe("bidding_system", () => {
       
       // Derive auction PDA
       [auctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), mint.publicKey.toBuffer()],
+        [Buffer.from("auction"), mint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
 
@@ -96,6 +249,9 @@ describe("bidding_system", () => {
         owner.publicKey
       );
 
+      // Get the vault holding the token during the auction
+      vault = await getAssociatedTokenAddress(mint.publicKey, auctionPda, true);
+
       // Derive metadata PDA
       [metadataPda] = PublicKey.findProgramAddressSync(
         [
@@ -110,13 +266,14 @@ describe("bidding_system", () => {
       const symbol = "GUITAR";
       const uri = "https://arweave.net/guitar-metadata";
 
+      const config = await auctionConfig(provider.connection, MAIN_AUCTION_SECS);
       const tx = await program.methods
-        .createAuction(name, symbol, uri)
+        .createAuction(name, symbol, uri, config, NO_ROYALTIES)
         .accounts({
           auction: auctionPda,
           mint: mint.publicKey,
           metadata: metadataPda,
-          tokenAccount: ownerTokenAccount,
+          vault: vault,
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -133,7 +290,38 @@ describe("bidding_system", () => {
       const auctionAccount = await readAuction(provider.connection, auctionPda);
       expect(auctionAccount.owner.toString()).to.equal(owner.publicKey.toString());
       expect(auctionAccount.tokenMint.toString()).to.equal(mint.publicKey.toString());
+      expect(auctionAccount.tokenAmount.toNumber()).to.equal(1);
+      expect(auctionAccount.minted).to.be.true;
+      expect(auctionAccount.nonce).to.equal(0);
       expect(auctionAccount.bidCount).to.equal(0);
+
+      // The token is held by the auction PDA's vault, not the owner
+      const vaultBalance = await provider.connection.getTokenAccountBalance(vault);
+      expect(vaultBalance.value.amount).to.equal("1");
+      const ownerTokenInfo = await provider.connection.getAccountInfo(ownerTokenAccount);
+      expect(ownerTokenInfo).to.be.null;
+    });
+
+    it("Hands the mint and freeze authorities to a max-supply-0 master edition", async () => {
+      const { masterEditionPda } = await deriveAuctionAccounts(
+        program.programId, mint.publicKey, owner.publicKey
+      );
+      const editionInfo = await provider.connection.getAccountInfo(masterEditionPda);
+      expect(editionInfo).to.not.be.null;
+      expect(editionInfo.owner.equals(MPL_TOKEN_METADATA_PROGRAM_ID)).to.be.true;
+
+      // Neither the owner nor anyone else can mint or freeze the auctioned token anymore
+      const mintInfo = await getMint(provider.connection, mint.publicKey);
+      expect(mintInfo.supply.toString()).to.equal("1");
+      expect(mintInfo.mintAuthority.equals(masterEditionPda)).to.be.true;
+      expect(mintInfo.freezeAuthority.equals(masterEditionPda)).to.be.true;
+
+      try {
+        await mintTo(provider.connection, owner, mint.publicKey, vault, owner, 1);
+        expect.fail("Should have failed");
+      } catch (error) {
+        expect(error.message).to.not.equal("Should have failed");
+      }
     });
 
     it("Fails to create auction with insufficient funds", async () => {
@@ -152,14 +340,10 @@ describe("bidding_system", () => {
       
       // Derive auction PDA
       const [poorAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), poorMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), poorMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
-
-      const poorOwnerTokenAccount = await getAssociatedTokenAddress(
-        poorMint.publicKey,
-        poorOwner.publicKey
-      );
+      const poorVault = await getAssociatedTokenAddress(poorMint.publicKey, poorAuctionPda, true);
 
       const [poorMetadataPda] = PublicKey.findProgramAddressSync(
         [
@@ -171,14 +355,15 @@ describe("bidding_system", () => {
       );
 
       // Try to create auction - should fail due to insufficient funds for rent
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       try {
         await program.methods
-          .createAuction("Poor Test", "POOR", "https://example.com/poor")
+          .createAuction("Poor Test", "POOR", "https://example.com/poor", config, NO_ROYALTIES)
           .accounts({
             auction: poorAuctionPda,
             mint: poorMint.publicKey,
             metadata: poorMetadataPda,
-            tokenAccount: poorOwnerTokenAccount,
+            vault: poorVault,
             owner: poorOwner.publicKey,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
             systemProgram: SystemProgram.programId,
@@ -203,14 +388,10 @@ describe("bidding_system", () => {
       const testMint = Keypair.generate();
       
       const [testAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), testMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), testMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
-
-      const testOwnerTokenAccount = await getAssociatedTokenAddress(
-        testMint.publicKey,
-        owner.publicKey
-      );
+      const testVault = await getAssociatedTokenAddress(testMint.publicKey, testAuctionPda, true);
 
       const [testMetadataPda] = PublicKey.findProgramAddressSync(
         [
@@ -226,13 +407,14 @@ describe("bidding_system", () => {
       console.log("Owner balance before:", balanceBefore);
 
       // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       await program.methods
-        .createAuction("Test Token", "TEST", "https://test.uri")
+        .createAuction("Test Token", "TEST", "https://test.uri", config, NO_ROYALTIES)
         .accounts({
           auction: testAuctionPda,
           mint: testMint.publicKey,
           metadata: testMetadataPda,
-          tokenAccount: testOwnerTokenAccount,
+          vault: testVault,
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -252,7 +434,8 @@ describe("bidding_system", () => {
         .accountsPartial({
           auction: testAuctionPda,
           tokenMint: testMint.publicKey,
-          ownerTokenAccount: testOwnerTokenAccount,
+          vault: testVault,
+          ownerTokenAccount: await getAssociatedTokenAddress(testMint.publicKey, owner.publicKey),
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -277,6 +460,62 @@ describe("bidding_system", () => {
       const mintInfo = await provider.connection.getAccountInfo(testMint.publicKey);
       expect(mintInfo).to.not.be.null;
     });
+
+    it("Fails to cancel an auction once it has a bid", async () => {
+      const cancelMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, cancelMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      await program.methods
+        .createAuction("Cancel With Bids", "CWB", "https://example.com/cancelbids", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: cancelMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, cancelMint])
+        .rpc();
+
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      const cancelAccounts = {
+        auction: accounts.auctionPda,
+        tokenMint: cancelMint.publicKey,
+        vault: accounts.vault,
+        ownerTokenAccount: accounts.ownerTokenAccount,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      };
+
+      try {
+        await program.methods.cancelAuction().accountsPartial(cancelAccounts).signers([owner]).rpc();
+        expect.fail("Should have failed with AuctionHasBids");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionHasBids");
+      }
+
+      // The auction and its bid are untouched
+      const auction = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auction.outcome).to.equal(AUCTION_OUTCOME_PENDING);
+      expect(auction.bidCount).to.equal(1);
+    });
   });
 
   describe("Place Bid", () => {
@@ -292,6 +531,7 @@ describe("bidding_system", () => {
           bidder: bidder1.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder1.publicKey, bidAmount))
         .signers([bidder1])
         .rpc();
 
@@ -299,13 +539,17 @@ describe("bidding_system", () => {
 
       const bidder1BalanceAfter = await provider.connection.getBalance(bidder1.publicKey);
       
-      // Verify lamports were escrowed
-      const auctionBalance = await provider.connection.getBalance(auctionPda);
-      expect(auctionBalance).to.be.greaterThanOrEqual(bidAmount.toNumber());
+      // Verify lamports were escrowed in the bidder's bid account on top of its rent
+      const bidAddress = bidPda(program.programId, auctionPda, bidder1.publicKey);
+      const bidBalance = await provider.connection.getBalance(bidAddress);
+      const bidRent = await accountRent(provider.connection, bidAddress);
+      expect(bidBalance).to.equal(bidRent + bidAmount.toNumber());
 
       // Verify the auction state was updated
       const auctionAccount = await readAuction(provider.connection, auctionPda);
       expect(auctionAccount.bidCount).to.equal(1);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder1.publicKey.toString());
+      expect(auctionAccount.topLamports.toString()).to.equal(bidAmount.toString());
       expect(auctionAccount.bids[0].bidder.toString()).to.equal(bidder1.publicKey.toString());
       expect(auctionAccount.bids[0].lamports.toString()).to.equal(bidAmount.toString());
     });
@@ -320,6 +564,7 @@ describe("bidding_system", () => {
           bidder: bidder1.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder1.publicKey, newBidAmount))
         .signers([bidder1])
         .rpc();
 
@@ -339,12 +584,14 @@ describe("bidding_system", () => {
           bidder: bidder2.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder2.publicKey, highBid))
         .signers([bidder2])
         .rpc();
 
       // Record balance before decreasing bid
+      const bidAddress = bidPda(program.programId, auctionPda, bidder2.publicKey);
       const balanceBefore = await provider.connection.getBalance(bidder2.publicKey);
-      const auctionBalanceBefore = await provider.connection.getBalance(auctionPda);
+      const bidBalanceBefore = await provider.connection.getBalance(bidAddress);
 
       // Now decrease the bid
       const lowBid = new anchor.BN(2_500_000); // 0.0025 SOL (half of previous)
@@ -355,12 +602,13 @@ describe("bidding_system", () => {
           bidder: bidder2.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder2.publicKey, lowBid))
         .signers([bidder2])
         .rpc();
 
       // Check balances after
       const balanceAfter = await provider.connection.getBalance(bidder2.publicKey);
-      const auctionBalanceAfter = await provider.connection.getBalance(auctionPda);
+      const bidBalanceAfter = await provider.connection.getBalance(bidAddress);
 
       // Verify bid was updated
       const auctionAccount = await readAuction(provider.connection, auctionPda);
@@ -376,9 +624,9 @@ describe("bidding_system", () => {
       expect(actualRefund).to.be.greaterThan(expectedRefund - 10_000); // Within 0.00001 SOL of expected
       expect(actualRefund).to.be.lessThan(expectedRefund + 10_000);
 
-      // Verify auction account balance decreased by the refund amount
-      const auctionBalanceChange = auctionBalanceBefore - auctionBalanceAfter;
-      expect(auctionBalanceChange).to.equal(expectedRefund);
+      // Verify bid account balance decreased by the refund amount
+      const bidBalanceChange = bidBalanceBefore - bidBalanceAfter;
+      expect(bidBalanceChange).to.equal(expectedRefund);
     });
 
     it("Allows multiple bidders", async () => {
@@ -389,6 +637,7 @@ describe("bidding_system", () => {
           bidder: bidder2.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder2.publicKey, new anchor.BN(3_000_000)))
         .signers([bidder2])
         .rpc();
 
@@ -399,25 +648,70 @@ describe("bidding_system", () => {
           bidder: bidder3.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder3.publicKey, new anchor.BN(4_000_000)))
         .signers([bidder3])
         .rpc();
 
       // Verify all bids were recorded
       const auctionAccount = await readAuction(provider.connection, auctionPda);
       expect(auctionAccount.bidCount).to.equal(3);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder3.publicKey.toString());
+      expect(auctionAccount.secondLamports.toNumber()).to.equal(3_000_000);
+    });
+
+    it("Hands the lead to the runner-up when the leading bid is lowered", async () => {
+      // bidder3 leads with 4_000_000 over bidder2 (3_000_000) and bidder1 (2_000_000)
+      await program.methods
+        .placeBid(new anchor.BN(2_500_000))
+        .accountsPartial({
+          auction: auctionPda,
+          bidder: bidder3.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder3.publicKey, new anchor.BN(2_500_000)))
+        .signers([bidder3])
+        .rpc();
+
+      let auctionAccount = await readAuction(provider.connection, auctionPda);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder2.publicKey.toString());
+      expect(auctionAccount.topLamports.toNumber()).to.equal(3_000_000);
+      expect(auctionAccount.secondLamports.toNumber()).to.equal(2_500_000);
+
+      // The ranking links bidder2 -> bidder3 -> bidder1
+      const ranked = (bidder: Keypair) => auctionAccount.bids.find((b) => b.bidder.equals(bidder.publicKey));
+      expect(ranked(bidder2).nextBidder.toString()).to.equal(bidder3.publicKey.toString());
+      expect(ranked(bidder3).nextBidder.toString()).to.equal(bidder1.publicKey.toString());
+      expect(ranked(bidder1).nextBidder.equals(PublicKey.default)).to.be.true;
+
+      // Raising it again takes the lead back
+      await program.methods
+        .placeBid(new anchor.BN(4_000_000))
+        .accountsPartial({
+          auction: auctionPda,
+          bidder: bidder3.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder3.publicKey, new anchor.BN(4_000_000)))
+        .signers([bidder3])
+        .rpc();
+
+      auctionAccount = await readAuction(provider.connection, auctionPda);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder3.publicKey.toString());
+      expect(auctionAccount.secondLamports.toNumber()).to.equal(3_000_000);
     });
 
-    it("Handles equal bids with insertion index tiebreaker", async () => {
+    it("Handles equal bids by keeping the earliest bid on top", async () => {
       // Create a new auction for this test
       const tiebreakerMint = Keypair.generate();
       const [tiebreakerAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), tiebreakerMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), tiebreakerMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
       const tiebreakerOwnerTokenAccount = await getAssociatedTokenAddress(
         tiebreakerMint.publicKey,
         owner.publicKey
       );
+      const tiebreakerVault = await getAssociatedTokenAddress(tiebreakerMint.publicKey, tiebreakerAuctionPda, true);
       const [tiebreakerMetadataPda] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("metadata"),
@@ -428,13 +722,14 @@ describe("bidding_system", () => {
       );
 
       // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       await program.methods
-        .createAuction("Tiebreaker Test", "TIE", "https://example.com/tie")
+        .createAuction("Tiebreaker Test", "TIE", "https://example.com/tie", config, NO_ROYALTIES)
         .accounts({
           auction: tiebreakerAuctionPda,
           mint: tiebreakerMint.publicKey,
           metadata: tiebreakerMetadataPda,
-          tokenAccount: tiebreakerOwnerTokenAccount,
+          vault: tiebreakerVault,
           owner: owner.publicKey,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
@@ -445,38 +740,26 @@ describe("bidding_system", () => {
         .signers([owner, tiebreakerMint])
         .rpc();
 
-      // Place three equal bids
+      // Bids must beat the top bid, so equal bids only arise by lowering: each bidder outbids the
+      // last, then the later two lower to the first bid and rank behind the bids they tie
       const equalBidAmount = new anchor.BN(5_000_000);
-      
-      await program.methods
-        .placeBid(equalBidAmount)
-        .accountsPartial({
-          auction: tiebreakerAuctionPda,
-          bidder: bidder1.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([bidder1])
-        .rpc();
-
-      await program.methods
-        .placeBid(equalBidAmount)
-        .accountsPartial({
-          auction: tiebreakerAuctionPda,
-          bidder: bidder2.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([bidder2])
-        .rpc();
+      const placeTieBid = async (bidder: Keypair, lamports: anchor.BN) =>
+        program.methods
+          .placeBid(lamports)
+          .accountsPartial({
+            auction: tiebreakerAuctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, tiebreakerAuctionPda, bidder.publicKey, lamports))
+          .signers([bidder])
+          .rpc();
 
-      await program.methods
-        .placeBid(equalBidAmount)
-        .accountsPartial({
-          auction: tiebreakerAuctionPda,
-          bidder: bidder3.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([bidder3])
-        .rpc();
+      await placeTieBid(bidder1, equalBidAmount);
+      await placeTieBid(bidder2, new anchor.BN(6_000_000));
+      await placeTieBid(bidder3, new anchor.BN(7_000_000));
+      await placeTieBid(bidder2, equalBidAmount);
+      await placeTieBid(bidder3, equalBidAmount);
 
       // Read auction state
       const auctionState = await readAuction(provider.connection, tiebreakerAuctionPda);
@@ -487,16 +770,11 @@ describe("bidding_system", () => {
         expect(bid.lamports.toString()).to.equal(equalBidAmount.toString());
       }
       
-      // Verify insertion indices are sequential (1, 2, 3)
-      const sortedBids = auctionState.bids.sort((a, b) => a.insertionIndex - b.insertionIndex);
-      expect(sortedBids[0].insertionIndex).to.equal(1);
-      expect(sortedBids[0].bidder.toString()).to.equal(bidder1.publicKey.toString());
-      expect(sortedBids[1].insertionIndex).to.equal(2);
-      expect(sortedBids[1].bidder.toString()).to.equal(bidder2.publicKey.toString());
-      expect(sortedBids[2].insertionIndex).to.equal(3);
-      expect(sortedBids[2].bidder.toString()).to.equal(bidder3.publicKey.toString());
-
-      // Conclude auction and verify bidder1 wins (lowest insertion_index)
+      // Verify the earliest bid keeps the lead on a tie
+      expect(auctionState.topBidder.toString()).to.equal(bidder1.publicKey.toString());
+      expect(auctionState.secondLamports.toString()).to.equal(equalBidAmount.toString());
+
+      // Conclude auction and verify bidder1 wins (earliest bid)
       const winnerTokenAccount = await getAssociatedTokenAddress(
         tiebreakerMint.publicKey,
         bidder1.publicKey
@@ -512,43 +790,62 @@ describe("bidding_system", () => {
       const createAtaTx = new anchor.web3.Transaction().add(createAtaIx);
       await provider.sendAndConfirm(createAtaTx, [owner]);
 
-      const nonWinnerAccounts = [
-        { pubkey: bidder2.publicKey, isWritable: true, isSigner: false },
-        { pubkey: bidder3.publicKey, isWritable: true, isSigner: false },
-      ];
-
+      const auctionRent = await accountRent(provider.connection, tiebreakerAuctionPda);
+      const vaultRent = await accountRent(provider.connection, tiebreakerVault);
       const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
 
+      await waitForAuctionEnd(provider.connection, tiebreakerAuctionPda);
+
       await program.methods
-        .concludeAuction()
+        .concludeAuction(null)
         .accountsPartial({
           auction: tiebreakerAuctionPda,
           tokenMint: tiebreakerMint.publicKey,
+          vault: tiebreakerVault,
           ownerTokenAccount: tiebreakerOwnerTokenAccount,
-          winnerTokenAccount: winnerTokenAccount,
           owner: owner.publicKey,
+          cranker: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts(nonWinnerAccounts)
         .signers([owner])
         .rpc();
 
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: tiebreakerAuctionPda,
+          tokenMint: tiebreakerMint.publicKey,
+          vault: tiebreakerVault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      await claimRefunds(program, tiebreakerAuctionPda, owner.publicKey, [
+        bidder2.publicKey,
+        bidder3.publicKey,
+      ]);
+
       const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
 
-      // Verify owner received winning bid (5_000_000 lamports) + rent refund (~4_788_480) minus gas fees
+      // Verify owner received winning bid (5_000_000 lamports) + auction and vault rent minus gas fees,
+      // less the rent of the auction record created by the owner's conclusion
       const balanceIncrease = ownerBalanceAfter - ownerBalanceBefore;
-      expect(balanceIncrease).to.be.greaterThan(9_700_000); // Account for gas fees
-      expect(balanceIncrease).to.be.lessThan(9_900_000);
+      const expectedIncrease = 5_000_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(balanceIncrease).to.be.greaterThan(expectedIncrease - 100_000); // Account for gas fees
+      expect(balanceIncrease).to.be.lessThanOrEqual(expectedIncrease);
 
       // Verify winner received the token
       const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
       expect(winnerTokenBalance.value.amount).to.equal("1");
 
-      // Verify owner token account is empty
-      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(tiebreakerOwnerTokenAccount);
-      expect(ownerTokenBalance.value.amount).to.equal("0");
+      // Verify the vault was emptied and closed
+      const vaultInfo = await provider.connection.getAccountInfo(tiebreakerVault);
+      expect(vaultInfo).to.be.null;
 
       // Verify auction was concluded
       const closedAuction = await provider.connection.getAccountInfo(tiebreakerAuctionPda);
@@ -559,13 +856,14 @@ describe("bidding_system", () => {
       // Create a new auction for this test
       const removeOldestMint = Keypair.generate();
       const [removeOldestAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), removeOldestMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), removeOldestMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
       const removeOldestOwnerTokenAccount = await getAssociatedTokenAddress(
         removeOldestMint.publicKey,
         owner.publicKey
       );
+      const removeOldestVault = await getAssociatedTokenAddress(removeOldestMint.publicKey, removeOldestAuctionPda, true);
       const [removeOldestMetadataPda] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("metadata"),
@@ -576,13 +874,14 @@ describe("bidding_system", () => {
       );
 
       // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       await program.methods
-        .createAuction("Remove Oldest Test", "RMV", "https://example.com/remove")
+        .createAuction("Remove Oldest Test", "RMV", "https://example.com/remove", config, NO_ROYALTIES)
         .accounts({
           auction: removeOldestAuctionPda,
           mint: removeOldestMint.publicKey,
           metadata: removeOldestMetadataPda,
-          tokenAccount: removeOldestOwnerTokenAccount,
+          vault: removeOldestVault,
           owner: owner.publicKey,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
@@ -593,75 +892,54 @@ describe("bidding_system", () => {
         .signers([owner, removeOldestMint])
         .rpc();
 
-      // Place three equal bids in order
+      // Tie three bids in order: each bidder outbids the last, then the later two lower to the first bid
       const equalBidAmount = new anchor.BN(8_000_000);
-      
-      // Bidder1 places first (insertion_index = 1)
-      await program.methods
-        .placeBid(equalBidAmount)
-        .accountsPartial({
-          auction: removeOldestAuctionPda,
-          bidder: bidder1.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([bidder1])
-        .rpc();
-
-      // Bidder2 places second (insertion_index = 2)
-      await program.methods
-        .placeBid(equalBidAmount)
-        .accountsPartial({
-          auction: removeOldestAuctionPda,
-          bidder: bidder2.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([bidder2])
-        .rpc();
+      const placeTieBid = async (bidder: Keypair, lamports: anchor.BN) =>
+        program.methods
+          .placeBid(lamports)
+          .accountsPartial({
+            auction: removeOldestAuctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, removeOldestAuctionPda, bidder.publicKey, lamports))
+          .signers([bidder])
+          .rpc();
 
-      // Bidder3 places third (insertion_index = 3)
-      await program.methods
-        .placeBid(equalBidAmount)
-        .accountsPartial({
-          auction: removeOldestAuctionPda,
-          bidder: bidder3.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([bidder3])
-        .rpc();
+      await placeTieBid(bidder1, equalBidAmount);
+      await placeTieBid(bidder2, new anchor.BN(9_000_000));
+      await placeTieBid(bidder3, new anchor.BN(10_000_000));
+      await placeTieBid(bidder2, equalBidAmount);
+      await placeTieBid(bidder3, equalBidAmount);
 
-      // Verify initial state: 3 equal bids with indices 1, 2, 3
+      // Verify initial state: 3 equal bids ranked in the order they were placed
       let auctionState = await readAuction(provider.connection, removeOldestAuctionPda);
       expect(auctionState.bidCount).to.equal(3);
-      
-      const sortedBids = auctionState.bids.sort((a, b) => a.insertionIndex - b.insertionIndex);
-      expect(sortedBids[0].insertionIndex).to.equal(1);
-      expect(sortedBids[0].bidder.toString()).to.equal(bidder1.publicKey.toString());
-      expect(sortedBids[1].insertionIndex).to.equal(2);
-      expect(sortedBids[1].bidder.toString()).to.equal(bidder2.publicKey.toString());
-      expect(sortedBids[2].insertionIndex).to.equal(3);
-      expect(sortedBids[2].bidder.toString()).to.equal(bidder3.publicKey.toString());
+      expect(auctionState.topBidder.toString()).to.equal(bidder1.publicKey.toString());
+      const bidOf = (bidder: Keypair) => auctionState.bids.find((b) => b.bidder.equals(bidder.publicKey));
+      expect(bidOf(bidder1).nextBidder.toString()).to.equal(bidder2.publicKey.toString());
+      expect(bidOf(bidder2).nextBidder.toString()).to.equal(bidder3.publicKey.toString());
+      expect(bidOf(bidder3).nextBidder.toString()).to.equal(PublicKey.default.toString());
 
-      // Cancel bidder1's bid (oldest, insertion_index = 1)
+      // Cancel bidder1's bid (oldest of the equal bids)
       await program.methods
         .cancelBid()
         .accountsPartial({
           auction: removeOldestAuctionPda,
           bidder: bidder1.publicKey,
         })
+        .remainingAccounts(await rankingAccounts(program, removeOldestAuctionPda, bidder1.publicKey, null))
         .signers([bidder1])
         .rpc();
 
-      // Verify state after cancellation: indices should be reordered to 1, 2
+      // Verify state after cancellation: bidder2 leads, bidder3 is the runner-up
       auctionState = await readAuction(provider.connection, removeOldestAuctionPda);
       expect(auctionState.bidCount).to.equal(2);
-      
-      const reorderedBids = auctionState.bids.sort((a, b) => a.insertionIndex - b.insertionIndex);
-      expect(reorderedBids[0].insertionIndex).to.equal(1);
-      expect(reorderedBids[0].bidder.toString()).to.equal(bidder2.publicKey.toString());
-      expect(reorderedBids[1].insertionIndex).to.equal(2);
-      expect(reorderedBids[1].bidder.toString()).to.equal(bidder3.publicKey.toString());
+      expect(auctionState.topBidder.toString()).to.equal(bidder2.publicKey.toString());
+      expect(auctionState.secondLamports.toString()).to.equal(equalBidAmount.toString());
+      expect(bidOf(bidder2).nextBidder.toString()).to.equal(bidder3.publicKey.toString());
 
-      // Conclude auction - bidder2 should win (now has lowest insertion_index)
+      // Conclude auction - bidder2 should win (now the oldest of the equal bids)
       const winnerTokenAccount = await getAssociatedTokenAddress(
         removeOldestMint.publicKey,
         bidder2.publicKey
@@ -677,42 +955,59 @@ describe("bidding_system", () => {
       const createAtaTx = new anchor.web3.Transaction().add(createAtaIx);
       await provider.sendAndConfirm(createAtaTx, [owner]);
 
-      const nonWinnerAccounts = [
-        { pubkey: bidder3.publicKey, isWritable: true, isSigner: false },
-      ];
-
+      const auctionRent = await accountRent(provider.connection, removeOldestAuctionPda);
+      const vaultRent = await accountRent(provider.connection, removeOldestVault);
       const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
 
+      await waitForAuctionEnd(provider.connection, removeOldestAuctionPda);
+
       await program.methods
-        .concludeAuction()
+        .concludeAuction(null)
         .accountsPartial({
           auction: removeOldestAuctionPda,
           tokenMint: removeOldestMint.publicKey,
+          vault: removeOldestVault,
           ownerTokenAccount: removeOldestOwnerTokenAccount,
-          winnerTokenAccount: winnerTokenAccount,
           owner: owner.publicKey,
+          cranker: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts(nonWinnerAccounts)
         .signers([owner])
         .rpc();
 
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: removeOldestAuctionPda,
+          tokenMint: removeOldestMint.publicKey,
+          vault: removeOldestVault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      await claimRefunds(program, removeOldestAuctionPda, owner.publicKey, [bidder3.publicKey]);
+
       const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
 
-      // Verify owner received winning bid (8_000_000 lamports) + rent refund (~4_788_480) minus gas fees
+      // Verify owner received winning bid (8_000_000 lamports) + auction and vault rent minus gas fees,
+      // less the rent of the auction record created by the owner's conclusion
       const balanceIncrease = ownerBalanceAfter - ownerBalanceBefore;
-      expect(balanceIncrease).to.be.greaterThan(12_700_000); // Account for gas fees
-      expect(balanceIncrease).to.be.lessThan(12_900_000);
+      const expectedIncrease = 8_000_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(balanceIncrease).to.be.greaterThan(expectedIncrease - 100_000); // Account for gas fees
+      expect(balanceIncrease).to.be.lessThanOrEqual(expectedIncrease);
 
       // Verify winner (bidder2) received the token
       const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
       expect(winnerTokenBalance.value.amount).to.equal("1");
 
-      // Verify owner token account is empty
-      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(removeOldestOwnerTokenAccount);
-      expect(ownerTokenBalance.value.amount).to.equal("0");
+      // Verify the vault was emptied and closed
+      const vaultInfo = await provider.connection.getAccountInfo(removeOldestVault);
+      expect(vaultInfo).to.be.null;
 
       // Verify auction was concluded
       const closedAuction = await provider.connection.getAccountInfo(removeOldestAuctionPda);
@@ -723,10 +1018,10 @@ describe("bidding_system", () => {
       // Create a new bidder with minimal funds
       const poorBidder = Keypair.generate();
       
-      // Airdrop only a tiny amount (not enough for a 1 SOL bid + fees)
+      // Airdrop enough for the bid account rent and fees, but not for a 1 SOL bid
       const signature = await provider.connection.requestAirdrop(
         poorBidder.publicKey,
-        100_000 // Only 0.0001 SOL
+        5_000_000 // Only 0.005 SOL
       );
       await provider.connection.confirmTransaction(signature);
 
@@ -739,6 +1034,7 @@ describe("bidding_system", () => {
             bidder: poorBidder.publicKey,
             systemProgram: SystemProgram.programId,
           })
+          .remainingAccounts(await rankingAccounts(program, auctionPda, poorBidder.publicKey, new anchor.BN(1_000_000_000)))
           .signers([poorBidder])
           .rpc();
         
@@ -752,7 +1048,7 @@ describe("bidding_system", () => {
       // Create a bidder with limited funds
       const limitedBidder = Keypair.generate();
       
-      // Airdrop enough for initial bid + fees but not enough for large increase
+      // Airdrop enough for initial bid, bid account rent and fees but not enough for large increase
       const signature = await provider.connection.requestAirdrop(
         limitedBidder.publicKey,
         3_000_000 // 0.003 SOL
@@ -767,6 +1063,7 @@ describe("bidding_system", () => {
           bidder: limitedBidder.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, limitedBidder.publicKey, new anchor.BN(1_000_000)))
         .signers([limitedBidder])
         .rpc();
 
@@ -779,6 +1076,7 @@ describe("bidding_system", () => {
             bidder: limitedBidder.publicKey,
             systemProgram: SystemProgram.programId,
           })
+          .remainingAccounts(await rankingAccounts(program, auctionPda, limitedBidder.publicKey, new anchor.BN(100_000_000_000)))
           .signers([limitedBidder])
           .rpc();
         
@@ -788,17 +1086,18 @@ describe("bidding_system", () => {
       }
     });
 
-    it("Places 10 bids (max capacity) and only allows conclude auction", async () => {
+    it("Keeps accepting bids and cancellations beyond 10 bidders", async () => {
       // Create a new auction for this test
       const maxBidsMint = Keypair.generate();
       const [maxBidsAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), maxBidsMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), maxBidsMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
       const maxBidsOwnerTokenAccount = await getAssociatedTokenAddress(
         maxBidsMint.publicKey,
         owner.publicKey
       );
+      const maxBidsVault = await getAssociatedTokenAddress(maxBidsMint.publicKey, maxBidsAuctionPda, true);
       const [maxBidsMetadataPda] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("metadata"),
@@ -809,13 +1108,14 @@ describe("bidding_system", () => {
       );
 
       // Create the auction
+      const config = await auctionConfig(provider.connection, MAX_BIDS_AUCTION_SECS);
       await program.methods
-        .createAuction("Max Bids Test", "MAXB", "https://example.com/maxbids")
+        .createAuction("Max Bids Test", "MAXB", "https://example.com/maxbids", config, NO_ROYALTIES)
         .accounts({
           auction: maxBidsAuctionPda,
           mint: maxBidsMint.publicKey,
           metadata: maxBidsMetadataPda,
-          tokenAccount: maxBidsOwnerTokenAccount,
+          vault: maxBidsVault,
           owner: owner.publicKey,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
@@ -841,6 +1141,7 @@ describe("bidding_system", () => {
             bidder: bidder.publicKey,
             systemProgram: SystemProgram.programId,
           })
+          .remainingAccounts(await rankingAccounts(program, maxBidsAuctionPda, bidder.publicKey, new anchor.BN((i + 1) * 1_000_000)))
           .signers([bidder])
           .rpc();
       }
@@ -849,58 +1150,50 @@ describe("bidding_system", () => {
       const fullAuction = await readAuction(provider.connection, maxBidsAuctionPda);
       expect(fullAuction.bidCount).to.equal(10);
 
-      // Try to place another bid - should fail with AuctionConcluded
+      // An 11th bidder can still join - there is no bid capacity
       const extraBidder = Keypair.generate();
       await airdrop(provider.connection, extraBidder.publicKey);
-      
-      try {
-        await program.methods
-          .placeBid(new anchor.BN(15_000_000))
-          .accountsPartial({
-            auction: maxBidsAuctionPda,
-            bidder: extraBidder.publicKey,
-            systemProgram: SystemProgram.programId,
-          })
-          .signers([extraBidder])
-          .rpc();
-        expect.fail("Should have failed with AuctionConcluded");
-      } catch (error) {
-        expect(error.toString()).to.include("AuctionConcluded");
-      }
 
-      // Try to update existing bid - should fail with AuctionConcluded
-      try {
-        await program.methods
-          .placeBid(new anchor.BN(20_000_000))
-          .accountsPartial({
-            auction: maxBidsAuctionPda,
-            bidder: maxBidders[0].publicKey,
-            systemProgram: SystemProgram.programId,
-          })
-          .signers([maxBidders[0]])
-          .rpc();
-        expect.fail("Should have failed with AuctionConcluded");
-      } catch (error) {
-        expect(error.toString()).to.include("AuctionConcluded");
-      }
+      await program.methods
+        .placeBid(new anchor.BN(15_000_000))
+        .accountsPartial({
+          auction: maxBidsAuctionPda,
+          bidder: extraBidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, maxBidsAuctionPda, extraBidder.publicKey, new anchor.BN(15_000_000)))
+        .signers([extraBidder])
+        .rpc();
 
-      // Try to cancel a bid - should fail with AuctionConcluded
-      try {
-        await program.methods
-          .cancelBid()
-          .accountsPartial({
-            auction: maxBidsAuctionPda,
-            bidder: maxBidders[0].publicKey,
-          })
-          .signers([maxBidders[0]])
-          .rpc();
-        expect.fail("Should have failed with AuctionConcluded");
-      } catch (error) {
-        expect(error.toString()).to.include("AuctionConcluded");
+      // No bid was evicted: all 11 bids stay ranked from the highest to the lowest
+      const rankedAuction = await readAuction(provider.connection, maxBidsAuctionPda);
+      expect(rankedAuction.bidCount).to.equal(11);
+      const ranking: string[] = [];
+      for (let key = rankedAuction.topBidder; !key.equals(PublicKey.default); ) {
+        ranking.push(key.toBase58());
+        key = rankedAuction.bids.find((b) => b.bidder.equals(key)).nextBidder;
       }
+      expect(ranking).to.deep.equal(
+        [extraBidder, ...maxBidders.slice().reverse()].map((bidder) => bidder.publicKey.toBase58())
+      );
+
+      // The lowest bid can still be cancelled
+      await program.methods
+        .cancelBid()
+        .accountsPartial({
+          auction: maxBidsAuctionPda,
+          bidder: maxBidders[0].publicKey,
+        })
+        .remainingAccounts(await rankingAccounts(program, maxBidsAuctionPda, maxBidders[0].publicKey, null))
+        .signers([maxBidders[0]])
+        .rpc();
+
+      const updatedAuction = await readAuction(provider.connection, maxBidsAuctionPda);
+      expect(updatedAuction.bidCount).to.equal(10);
+      expect(updatedAuction.topBidder.toString()).to.equal(extraBidder.publicKey.toString());
 
       // Conclude auction should succeed
-      const winner = maxBidders[9]; // Last bidder has highest bid (10 * 1_000_000)
+      const winner = extraBidder; // Extra bidder has highest bid (15_000_000)
       const winnerTokenAccount = await getAssociatedTokenAddress(
         maxBidsMint.publicKey,
         winner.publicKey
@@ -916,48 +1209,97 @@ describe("bidding_system", () => {
       const createAtaTx = new anchor.web3.Transaction().add(createAtaIx);
       await provider.sendAndConfirm(createAtaTx, [owner]);
 
-      // Collect all non-winner bidders for remaining_accounts
-      const nonWinnerAccounts = [];
-      for (let i = 0; i < 9; i++) {
-        nonWinnerAccounts.push({
-          pubkey: maxBidders[i].publicKey,
-          isWritable: true,
-          isSigner: false,
-        });
-      }
-
+      const auctionRent = await accountRent(provider.connection, maxBidsAuctionPda);
+      const vaultRent = await accountRent(provider.connection, maxBidsVault);
       const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
 
+      await waitForAuctionEnd(provider.connection, maxBidsAuctionPda);
+
       await program.methods
-        .concludeAuction()
+        .concludeAuction(null)
         .accountsPartial({
           auction: maxBidsAuctionPda,
           tokenMint: maxBidsMint.publicKey,
+          vault: maxBidsVault,
           ownerTokenAccount: maxBidsOwnerTokenAccount,
-          winnerTokenAccount: winnerTokenAccount,
           owner: owner.publicKey,
+          cranker: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts(nonWinnerAccounts)
         .signers([owner])
         .rpc();
 
-      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
-
-      // Verify owner received winning bid (10_000_000 lamports) + rent refund (~4_788_480) minus gas fees
-      const balanceIncrease = ownerBalanceAfter - ownerBalanceBefore;
-      expect(balanceIncrease).to.be.greaterThan(14_700_000); // Account for gas fees
-      expect(balanceIncrease).to.be.lessThan(14_900_000);
+      // A page containing the winning bid is rejected
+      try {
+        await program.methods
+          .settleBatch()
+          .accountsPartial({ auction: maxBidsAuctionPda, owner: owner.publicKey })
+          .remainingAccounts(
+            bidAccounts(program.programId, maxBidsAuctionPda, [maxBidders[1].publicKey, winner.publicKey])
+          )
+          .rpc();
 
-      // Verify winner received the token
-      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
-      expect(winnerTokenBalance.value.amount).to.equal("1");
+        expect.fail("Should have failed with WinningBidNotRefundable");
+      } catch (error) {
+        expect(error.toString()).to.include("WinningBidNotRefundable");
+      }
 
-      // Verify owner token account is empty
-      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(maxBidsOwnerTokenAccount);
-      expect(ownerTokenBalance.value.amount).to.equal("0");
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: maxBidsAuctionPda,
+          tokenMint: maxBidsMint.publicKey,
+          vault: maxBidsVault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: winner.publicKey,
+          payer: winner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner])
+        .rpc();
+
+      // The auction stays open until every losing bid has been refunded
+      let claimingAuction = await readAuction(provider.connection, maxBidsAuctionPda);
+      expect(claimingAuction.bidCount).to.equal(9);
+
+      // Refund the losing bids a page at a time
+      const losers = maxBidders.slice(1).map((bidder) => bidder.publicKey);
+      for (let page = 0; page < losers.length; page += 4) {
+        await program.methods
+          .settleBatch()
+          .accountsPartial({ auction: maxBidsAuctionPda, owner: owner.publicKey })
+          .remainingAccounts(bidAccounts(program.programId, maxBidsAuctionPda, losers.slice(page, page + 4)))
+          .rpc();
+
+        // Each refunded bid account is closed
+        for (const loser of losers.slice(page, page + 4)) {
+          const bidInfo = await provider.connection.getAccountInfo(bidPda(program.programId, maxBidsAuctionPda, loser));
+          expect(bidInfo).to.be.null;
+        }
+
+        if (page + 4 < losers.length) {
+          claimingAuction = await readAuction(provider.connection, maxBidsAuctionPda);
+          expect(claimingAuction.bidCount).to.equal(losers.length - page - 4);
+        }
+      }
+
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+
+      // Verify owner received winning bid (15_000_000 lamports) + auction and vault rent minus gas fees,
+      // less the rent of the auction record created by the owner's conclusion
+      const balanceIncrease = ownerBalanceAfter - ownerBalanceBefore;
+      const expectedIncrease = 15_000_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(balanceIncrease).to.be.greaterThan(expectedIncrease - 100_000); // Account for gas fees
+      expect(balanceIncrease).to.be.lessThanOrEqual(expectedIncrease);
+
+      // Verify winner received the token
+      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
+      expect(winnerTokenBalance.value.amount).to.equal("1");
+
+      // Verify the vault was emptied and closed
+      const vaultInfo = await provider.connection.getAccountInfo(maxBidsVault);
+      expect(vaultInfo).to.be.null;
 
       // Verify auction was concluded and closed
       const closedAuction = await provider.connection.getAccountInfo(maxBidsAuctionPda);
@@ -975,6 +1317,7 @@ describe("bidding_system", () => {
           auction: auctionPda,
           bidder: bidder1.publicKey,
         })
+        .remainingAccounts(await rankingAccounts(program, auctionPda, bidder1.publicKey, null))
         .signers([bidder1])
         .rpc();
 
@@ -993,13 +1336,10 @@ describe("bidding_system", () => {
       // Create a new auction for this test
       const doubleCancelMint = Keypair.generate();
       const [doubleCancelAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), doubleCancelMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), doubleCancelMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
-      const doubleCancelOwnerTokenAccount = await getAssociatedTokenAddress(
-        doubleCancelMint.publicKey,
-        owner.publicKey
-      );
+      const doubleCancelVault = await getAssociatedTokenAddress(doubleCancelMint.publicKey, doubleCancelAuctionPda, true);
       const [doubleCancelMetadataPda] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("metadata"),
@@ -1010,13 +1350,14 @@ describe("bidding_system", () => {
       );
 
       // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       await program.methods
-        .createAuction("Double Cancel Test", "DBLC", "https://example.com/double")
+        .createAuction("Double Cancel Test", "DBLC", "https://example.com/double", config, NO_ROYALTIES)
         .accounts({
           auction: doubleCancelAuctionPda,
           mint: doubleCancelMint.publicKey,
           metadata: doubleCancelMetadataPda,
-          tokenAccount: doubleCancelOwnerTokenAccount,
+          vault: doubleCancelVault,
           owner: owner.publicKey,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
@@ -1035,6 +1376,7 @@ describe("bidding_system", () => {
           bidder: bidder1.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, doubleCancelAuctionPda, bidder1.publicKey, new anchor.BN(3_000_000)))
         .signers([bidder1])
         .rpc();
 
@@ -1045,6 +1387,7 @@ describe("bidding_system", () => {
           auction: doubleCancelAuctionPda,
           bidder: bidder1.publicKey,
         })
+        .remainingAccounts(await rankingAccounts(program, doubleCancelAuctionPda, bidder1.publicKey, null))
         .signers([bidder1])
         .rpc();
 
@@ -1056,12 +1399,14 @@ describe("bidding_system", () => {
             auction: doubleCancelAuctionPda,
             bidder: bidder1.publicKey,
           })
+          .remainingAccounts(await rankingAccounts(program, doubleCancelAuctionPda, bidder1.publicKey, null))
           .signers([bidder1])
           .rpc();
         
-        expect.fail("Should have failed with NoBidFound on second cancel");
+        expect.fail("Should have failed with AccountNotInitialized on second cancel");
       } catch (error) {
-        expect(error.toString()).to.include("NoBidFound");
+        // The first cancellation closed the bid account
+        expect(error.toString()).to.include("AccountNotInitialized");
       }
     });
 
@@ -1069,13 +1414,10 @@ describe("bidding_system", () => {
       // Create a new auction for this test
       const noBidMint = Keypair.generate();
       const [noBidAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), noBidMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), noBidMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
-      const noBidOwnerTokenAccount = await getAssociatedTokenAddress(
-        noBidMint.publicKey,
-        owner.publicKey
-      );
+      const noBidVault = await getAssociatedTokenAddress(noBidMint.publicKey, noBidAuctionPda, true);
       const [noBidMetadataPda] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("metadata"),
@@ -1086,13 +1428,14 @@ describe("bidding_system", () => {
       );
 
       // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       await program.methods
-        .createAuction("No Bid Test", "NOBD", "https://example.com/nobid")
+        .createAuction("No Bid Test", "NOBD", "https://example.com/nobid", config, NO_ROYALTIES)
         .accounts({
           auction: noBidAuctionPda,
           mint: noBidMint.publicKey,
           metadata: noBidMetadataPda,
-          tokenAccount: noBidOwnerTokenAccount,
+          vault: noBidVault,
           owner: owner.publicKey,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
@@ -1111,6 +1454,7 @@ describe("bidding_system", () => {
           bidder: bidder1.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, noBidAuctionPda, bidder1.publicKey, new anchor.BN(5_000_000)))
         .signers([bidder1])
         .rpc();
 
@@ -1122,12 +1466,14 @@ describe("bidding_system", () => {
             auction: noBidAuctionPda,
             bidder: bidder2.publicKey,
           })
+          .remainingAccounts(await rankingAccounts(program, noBidAuctionPda, bidder2.publicKey, null))
           .signers([bidder2])
           .rpc();
         
-        expect.fail("Should have failed with NoBidFound");
+        expect.fail("Should have failed with AccountNotInitialized");
       } catch (error) {
-        expect(error.toString()).to.include("NoBidFound");
+        // bidder2 has no bid account
+        expect(error.toString()).to.include("AccountNotInitialized");
       }
     });
 
@@ -1135,13 +1481,14 @@ describe("bidding_system", () => {
       // Create a new auction for this test
       const cancelWinnerMint = Keypair.generate();
       const [cancelWinnerAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), cancelWinnerMint.publicKey.toBuffer()],
+        [Buffer.from("auction"), cancelWinnerMint.publicKey.toBuffer(), auctionNonce(0)],
         program.programId
       );
       const cancelWinnerOwnerTokenAccount = await getAssociatedTokenAddress(
         cancelWinnerMint.publicKey,
         owner.publicKey
       );
+      const cancelWinnerVault = await getAssociatedTokenAddress(cancelWinnerMint.publicKey, cancelWinnerAuctionPda, true);
       const [cancelWinnerMetadataPda] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("metadata"),
@@ -1152,13 +1499,14 @@ describe("bidding_system", () => {
       );
 
       // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
       await program.methods
-        .createAuction("Cancel Winner Test", "CANW", "https://example.com/cancelwinner")
+        .createAuction("Cancel Winner Test", "CANW", "https://example.com/cancelwinner", config, NO_ROYALTIES)
         .accounts({
           auction: cancelWinnerAuctionPda,
           mint: cancelWinnerMint.publicKey,
           metadata: cancelWinnerMetadataPda,
-          tokenAccount: cancelWinnerOwnerTokenAccount,
+          vault: cancelWinnerVault,
           owner: owner.publicKey,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
@@ -1177,6 +1525,7 @@ describe("bidding_system", () => {
           bidder: bidder1.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, cancelWinnerAuctionPda, bidder1.publicKey, new anchor.BN(3_000_000)))
         .signers([bidder1])
         .rpc();
 
@@ -1187,6 +1536,7 @@ describe("bidding_system", () => {
           bidder: bidder2.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, cancelWinnerAuctionPda, bidder2.publicKey, new anchor.BN(5_000_000)))
         .signers([bidder2])
         .rpc();
 
@@ -1197,6 +1547,7 @@ describe("bidding_system", () => {
           bidder: bidder3.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, cancelWinnerAuctionPda, bidder3.publicKey, new anchor.BN(7_000_000)))
         .signers([bidder3])
         .rpc();
 
@@ -1211,6 +1562,7 @@ describe("bidding_system", () => {
           auction: cancelWinnerAuctionPda,
           bidder: bidder3.publicKey,
         })
+        .remainingAccounts(await rankingAccounts(program, cancelWinnerAuctionPda, bidder3.publicKey, null))
         .signers([bidder3])
         .rpc();
 
@@ -1235,45 +1587,59 @@ describe("bidding_system", () => {
       const createAtaTx = new anchor.web3.Transaction().add(createAtaIx);
       await provider.sendAndConfirm(createAtaTx, [owner]);
 
-      // Collect remaining bidder (bidder1) for refund
-      const remainingBidderAccounts = [{
-        pubkey: bidder1.publicKey,
-        isWritable: true,
-        isSigner: false,
-      }];
-
+      const auctionRent = await accountRent(provider.connection, cancelWinnerAuctionPda);
+      const vaultRent = await accountRent(provider.connection, cancelWinnerVault);
       const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
 
+      await waitForAuctionEnd(provider.connection, cancelWinnerAuctionPda);
+
       await program.methods
-        .concludeAuction()
+        .concludeAuction(null)
         .accountsPartial({
           auction: cancelWinnerAuctionPda,
           tokenMint: cancelWinnerMint.publicKey,
+          vault: cancelWinnerVault,
           ownerTokenAccount: cancelWinnerOwnerTokenAccount,
-          winnerTokenAccount: newWinnerTokenAccount,
           owner: owner.publicKey,
+          cranker: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts(remainingBidderAccounts)
         .signers([owner])
         .rpc();
 
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: cancelWinnerAuctionPda,
+          tokenMint: cancelWinnerMint.publicKey,
+          vault: cancelWinnerVault,
+          winnerTokenAccount: newWinnerTokenAccount,
+          owner: owner.publicKey,
+          winner: newWinner.publicKey,
+          payer: newWinner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([newWinner])
+        .rpc();
+
+      await claimRefunds(program, cancelWinnerAuctionPda, owner.publicKey, [bidder1.publicKey]);
+
       const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
 
-      // Verify owner received winning bid (5_000_000 lamports) + rent refund (~4_788_480) minus gas fees
+      // Verify owner received winning bid (5_000_000 lamports) + auction and vault rent minus gas fees,
+      // less the rent of the auction record created by the owner's conclusion
       const balanceIncrease = ownerBalanceAfter - ownerBalanceBefore;
-      expect(balanceIncrease).to.be.greaterThan(9_700_000); // Account for gas fees
-      expect(balanceIncrease).to.be.lessThan(9_900_000);
+      const expectedIncrease = 5_000_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(balanceIncrease).to.be.greaterThan(expectedIncrease - 100_000); // Account for gas fees
+      expect(balanceIncrease).to.be.lessThanOrEqual(expectedIncrease);
 
       // Verify new winner (bidder2) received the token
       const winnerTokenBalance = await provider.connection.getTokenAccountBalance(newWinnerTokenAccount);
       expect(winnerTokenBalance.value.amount).to.equal("1");
 
-      // Verify owner token account is empty
-      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(cancelWinnerOwnerTokenAccount);
-      expect(ownerTokenBalance.value.amount).to.equal("0");
+      // Verify the vault was emptied and closed
+      const vaultInfo = await provider.connection.getAccountInfo(cancelWinnerVault);
+      expect(vaultInfo).to.be.null;
 
       // Verify auction was concluded
       const closedAuction = await provider.connection.getAccountInfo(cancelWinnerAuctionPda);
@@ -1286,16 +1652,8 @@ describe("bidding_system", () => {
       // Read the auction using our helper
       const auctionAccount = await readAuction(provider.connection, auctionPda);
       
-      // Find the winner (highest bidder)
-      let highestBid = new anchor.BN(0);
-      let winnerPubkey = null;
-      
-      for (let i = 0; i < auctionAccount.bidCount; i++) {
-        if (auctionAccount.bids[i].lamports.gt(highestBid)) {
-          highestBid = auctionAccount.bids[i].lamports;
-          winnerPubkey = auctionAccount.bids[i].bidder;
-        }
-      }
+      // The winner is the top bidder tracked on the auction
+      const winnerPubkey = auctionAccount.topBidder;
 
       // Get winner's token account
       const winnerTokenAccount = await getAssociatedTokenAddress(
@@ -1313,91 +1671,188 @@ describe("bidding_system", () => {
       const createAtaTx = new anchor.web3.Transaction().add(createAtaIx);
       await provider.sendAndConfirm(createAtaTx, [owner]);
 
-      // Collect all bidder pubkeys for remaining_accounts (excluding winner)
-      const bidderAccounts = [];
-      for (let i = 0; i < auctionAccount.bidCount; i++) {
-        const bidderPubkey = auctionAccount.bids[i].bidder;
-        if (!bidderPubkey.equals(PublicKey.default) && !bidderPubkey.equals(winnerPubkey)) {
-          bidderAccounts.push({
-            pubkey: bidderPubkey,
-            isWritable: true,
-            isSigner: false,
-          });
-        }
-      }
+      const winner = [bidder1, bidder2, bidder3].find((bidder) => bidder.publicKey.equals(winnerPubkey));
+      const loser = [bidder1, bidder2, bidder3].find(
+        (bidder) =>
+          !bidder.publicKey.equals(winnerPubkey) &&
+          auctionAccount.bids.some((bid) => bid.bidder.equals(bidder.publicKey))
+      );
+      const losers = auctionAccount.bids
+        .map((bid) => bid.bidder)
+        .filter((bidder) => !bidder.equals(winnerPubkey));
 
+      const auctionRent = await accountRent(provider.connection, auctionPda);
+      const vaultRent = await accountRent(provider.connection, vault);
       const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
 
+      await waitForAuctionEnd(provider.connection, auctionPda);
+
+      // Refunds only open once the outcome is recorded
+      try {
+        await claimRefunds(program, auctionPda, owner.publicKey, [losers[0]]);
+        expect.fail("Should have failed with AuctionNotConcluded");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionNotConcluded");
+      }
+
       await program.methods
-        .concludeAuction()
+        .concludeAuction(null)
         .accountsPartial({
           auction: auctionPda,
           tokenMint: mint.publicKey,
+          vault: vault,
           ownerTokenAccount: ownerTokenAccount,
-          winnerTokenAccount: winnerTokenAccount,
           owner: owner.publicKey,
+          cranker: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts(bidderAccounts)
         .signers([owner])
         .rpc();
 
+      const concludedAuction = await readAuction(provider.connection, auctionPda);
+      expect(concludedAuction.outcome).to.equal(AUCTION_OUTCOME_BID_WON);
+      expect(concludedAuction.saleLamports.toString()).to.equal("4000000");
+
+      // The winning bid is settled by claim_item, not refunded
+      try {
+        await claimRefunds(program, auctionPda, owner.publicKey, [winnerPubkey]);
+        expect.fail("Should have failed with WinningBidNotRefundable");
+      } catch (error) {
+        expect(error.toString()).to.include("WinningBidNotRefundable");
+      }
+
+      // The token can only be delivered to the winner
+      try {
+        await program.methods
+          .claimItem()
+          .accountsPartial({
+            auction: auctionPda,
+            tokenMint: mint.publicKey,
+            vault: vault,
+            winnerTokenAccount: await getAssociatedTokenAddress(mint.publicKey, loser.publicKey),
+            owner: owner.publicKey,
+            winner: loser.publicKey,
+            payer: loser.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([loser])
+          .rpc();
+        expect.fail("Should have failed with InvalidWinner");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidWinner");
+      }
+
+      // Each loser claims its own refund: escrow plus bid account rent
+      const loserBid = bidPda(program.programId, auctionPda, losers[0]);
+      const loserEscrow = auctionAccount.bids.find((bid) => bid.bidder.equals(losers[0])).lamports;
+      const loserRefund = (await provider.connection.getAccountInfo(loserBid)).lamports;
+      const loserBalanceBefore = await provider.connection.getBalance(losers[0]);
+      await claimRefunds(program, auctionPda, owner.publicKey, [losers[0]]);
+      const loserBalanceAfter = await provider.connection.getBalance(losers[0]);
+      expect(loserBalanceAfter - loserBalanceBefore).to.equal(loserRefund);
+      expect(loserRefund).to.equal(
+        loserEscrow.toNumber() + (await provider.connection.getMinimumBalanceForRentExemption(162))
+      );
+
+      // Anyone may deliver the token to the winner
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: auctionPda,
+          tokenMint: mint.publicKey,
+          vault: vault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: winner.publicKey,
+          payer: loser.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([loser])
+        .rpc();
+
+      // The last claim closes the auction
+      await claimRefunds(program, auctionPda, owner.publicKey, losers.slice(1));
+
       const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
 
-      // Verify owner received winning bid (4_000_000 lamports) + rent refund (~4_788_480) minus gas fees
+      // Verify owner received winning bid (4_000_000 lamports) + auction and vault rent minus gas fees,
+      // less the rent of the auction record created by the owner's conclusion
       // Winner had 4_000_000 lamports
       const balanceIncrease = ownerBalanceAfter - ownerBalanceBefore;
-      expect(balanceIncrease).to.be.greaterThan(8_700_000); // Account for gas fees
-      expect(balanceIncrease).to.be.lessThan(8_900_000);
+      const expectedIncrease = 4_000_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(balanceIncrease).to.be.greaterThan(expectedIncrease - 100_000); // Account for gas fees
+      expect(balanceIncrease).to.be.lessThanOrEqual(expectedIncrease);
 
       // Verify winner received the token
       const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
       expect(winnerTokenBalance.value.amount).to.equal("1");
 
-      // Verify owner token account is empty
-      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(ownerTokenAccount);
-      expect(ownerTokenBalance.value.amount).to.equal("0");
+      // Verify the vault was emptied and closed
+      const vaultInfo = await provider.connection.getAccountInfo(vault);
+      expect(vaultInfo).to.be.null;
 
       // Verify auction account is closed
       const auctionInfo = await provider.connection.getAccountInfo(auctionPda);
       expect(auctionInfo).to.be.null;
+
+      // Verify the sale outlives the auction in its provenance record
+      const record = await program.account.auctionRecord.fetch(recordPda(program.programId, auctionPda));
+      expect(record.auction.toString()).to.equal(auctionPda.toString());
+      expect(record.tokenMint.toString()).to.equal(mint.publicKey.toString());
+      expect(record.seller.toString()).to.equal(owner.publicKey.toString());
+      expect(record.winner.toString()).to.equal(winnerPubkey.toString());
+      expect(record.price.toNumber()).to.equal(4_000_000);
+      expect(record.paymentMint.toString()).to.equal(PublicKey.default.toString());
     });
   });
 
-  describe("Conclude Auction - No Bidders", () => {
-    it("Fails to conclude auction immediately after creation (no bids)", async () => {
-      // Create a new auction for this test
-      const noBidsMint = Keypair.generate();
-      
-      const [noBidsAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), noBidsMint.publicKey.toBuffer()],
-        program.programId
-      );
+  describe("Auction Time Window", () => {
+    it("Fails to create auction that ends before it starts", async () => {
+      const windowMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, windowMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.startTs = config.endTs.addn(30);
 
-      const noBidsOwnerTokenAccount = await getAssociatedTokenAddress(
-        noBidsMint.publicKey,
-        owner.publicKey
-      );
+      try {
+        await program.methods
+          .createAuction("Bad Window", "BADW", "https://example.com/badwindow", config, NO_ROYALTIES)
+          .accounts({
+            auction: accounts.auctionPda,
+            mint: windowMint.publicKey,
+            metadata: accounts.metadataPda,
+            vault: accounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, windowMint])
+          .rpc();
 
-      const [noBidsMetadataPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("metadata"),
-          MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-          noBidsMint.publicKey.toBuffer(),
-        ],
-        MPL_TOKEN_METADATA_PROGRAM_ID
-      );
+        expect.fail("Should have failed with InvalidAuctionWindow");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAuctionWindow");
+      }
+    });
+
+    it("Rejects bids before the start time", async () => {
+      const futureMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, futureMint.publicKey, owner.publicKey);
+      const now = await clusterTime(provider.connection);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.startTs = new anchor.BN(now + 3600);
+      config.endTs = new anchor.BN(now + 7200);
+      config.maxEndTs = config.endTs;
 
-      // Create auction
       await program.methods
-        .createAuction("No Bids Token", "NOBID", "https://nobids.uri")
+        .createAuction("Future Auction", "FUTR", "https://example.com/future", config, NO_ROYALTIES)
         .accounts({
-          auction: noBidsAuctionPda,
-          mint: noBidsMint.publicKey,
-          metadata: noBidsMetadataPda,
-          tokenAccount: noBidsOwnerTokenAccount,
+          auction: accounts.auctionPda,
+          mint: futureMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -1405,78 +1860,118 @@ describe("bidding_system", () => {
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
-        .signers([owner, noBidsMint])
+        .signers([owner, futureMint])
         .rpc();
 
-      // Try to conclude auction with no bids
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.startTs.toNumber()).to.equal(now + 3600);
+      expect(auctionAccount.endTs.toNumber()).to.equal(now + 7200);
+
       try {
         await program.methods
-          .concludeAuction()
+          .placeBid(new anchor.BN(1_000_000))
           .accountsPartial({
-            auction: noBidsAuctionPda,
-            tokenMint: noBidsMint.publicKey,
-            ownerTokenAccount: noBidsOwnerTokenAccount,
-            winnerTokenAccount: noBidsOwnerTokenAccount, // Dummy account
-            owner: owner.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .remainingAccounts([])
-          .signers([owner])
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(1_000_000)))
+          .signers([bidder1])
           .rpc();
 
-        expect.fail("Should have failed with NoActiveBids");
+        expect.fail("Should have failed with AuctionNotStarted");
       } catch (error) {
-        expect(error.toString()).to.include("NoActiveBids");
+        expect(error.toString()).to.include("AuctionNotStarted");
       }
+    });
+
+    it("Rejects bids and cancellations after the end time", async () => {
+      const endedMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, endedMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
 
-      // Clean up - cancel auction
       await program.methods
-        .cancelAuction()
-        .accountsPartial({
-          auction: noBidsAuctionPda,
-          tokenMint: noBidsMint.publicKey,
-          ownerTokenAccount: noBidsOwnerTokenAccount,
+        .createAuction("Ended Auction", "ENDD", "https://example.com/ended", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: endedMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
-        .signers([owner])
+        .signers([owner, endedMint])
         .rpc();
-    });
 
-    it("Fails to conclude auction after bid is placed and cancelled", async () => {
-      // Create another auction for this test
-      const cancelledBidMint = Keypair.generate();
-      
-      const [cancelledBidAuctionPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("auction"), cancelledBidMint.publicKey.toBuffer()],
-        program.programId
-      );
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
 
-      const cancelledBidOwnerTokenAccount = await getAssociatedTokenAddress(
-        cancelledBidMint.publicKey,
-        owner.publicKey
-      );
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
 
-      const [cancelledBidMetadataPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("metadata"),
-          MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-          cancelledBidMint.publicKey.toBuffer(),
-        ],
-        MPL_TOKEN_METADATA_PROGRAM_ID
-      );
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(3_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(3_000_000)))
+          .signers([bidder2])
+          .rpc();
+
+        expect.fail("Should have failed with AuctionEnded");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionEnded");
+      }
+
+      try {
+        await program.methods
+          .cancelBid()
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, null))
+          .signers([bidder1])
+          .rpc();
+
+        expect.fail("Should have failed with AuctionEnded");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionEnded");
+      }
+    });
+  });
+
+  describe("Soft Close", () => {
+    it("Extends the end time for late bids up to the hard cap", async () => {
+      const softCloseMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, softCloseMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, MAX_BIDS_AUCTION_SECS);
+      // Every bid lands in the extension window; two extensions hit the cap
+      config.extensionWindowSecs = new anchor.BN(MAX_BIDS_AUCTION_SECS);
+      config.extensionSecs = new anchor.BN(20);
+      config.maxEndTs = config.endTs.addn(30);
 
-      // Create auction
       await program.methods
-        .createAuction("Cancelled Bid Token", "CANCEL", "https://cancelled.uri")
+        .createAuction("Soft Close Test", "SOFT", "https://example.com/soft", config, NO_ROYALTIES)
         .accounts({
-          auction: cancelledBidAuctionPda,
-          mint: cancelledBidMint.publicKey,
-          metadata: cancelledBidMetadataPda,
-          tokenAccount: cancelledBidOwnerTokenAccount,
+          auction: accounts.auctionPda,
+          mint: softCloseMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -1484,54 +1979,3108 @@ describe("bidding_system", () => {
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
-        .signers([owner, cancelledBidMint])
+        .signers([owner, softCloseMint])
         .rpc();
 
-      // Place a bid
+      // First late bid pushes the end time back by extensionSecs
       await program.methods
-        .placeBid(new anchor.BN(5_000_000))
+        .placeBid(new anchor.BN(1_000_000))
         .accountsPartial({
-          auction: cancelledBidAuctionPda,
+          auction: accounts.auctionPda,
           bidder: bidder1.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(1_000_000)))
         .signers([bidder1])
         .rpc();
 
-      // Verify bid was placed
-      let auctionAccount = await readAuction(provider.connection, cancelledBidAuctionPda);
-      expect(auctionAccount.bidCount).to.equal(1);
+      let auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.endTs.toString()).to.equal(config.endTs.addn(20).toString());
 
-      // Cancel the bid
+      // Second late bid is capped at maxEndTs
       await program.methods
-        .cancelBid()
+        .placeBid(new anchor.BN(2_000_000))
         .accountsPartial({
-          auction: cancelledBidAuctionPda,
-          bidder: bidder1.publicKey,
+          auction: accounts.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
         })
-        .signers([bidder1])
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder2])
         .rpc();
 
-      // Verify bid was cancelled
-      auctionAccount = await readAuction(provider.connection, cancelledBidAuctionPda);
-      expect(auctionAccount.bidCount).to.equal(0);
+      auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.endTs.toString()).to.equal(config.maxEndTs.toString());
+      expect(auctionAccount.maxEndTs.toString()).to.equal(config.maxEndTs.toString());
+    });
 
-      // Try to conclude auction with no active bids
-      try {
+    it("Only extends the end time for late bids that raise the stakes", async () => {
+      const softCloseMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, softCloseMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, MAX_BIDS_AUCTION_SECS);
+      config.extensionWindowSecs = new anchor.BN(MAX_BIDS_AUCTION_SECS);
+      config.extensionSecs = new anchor.BN(20);
+      config.maxEndTs = config.endTs.addn(100);
+
+      await program.methods
+        .createAuction("Soft Close Lower", "SOFTL", "https://example.com/softlower", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: softCloseMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, softCloseMint])
+        .rpc();
+
+      for (const [bidder, lamports] of [
+        [bidder1, 3_000_000],
+        [bidder2, 4_000_000],
+        [bidder3, 5_000_000],
+      ] as const) {
         await program.methods
-          .concludeAuction()
+          .placeBid(new anchor.BN(lamports))
           .accountsPartial({
-            auction: cancelledBidAuctionPda,
-            tokenMint: cancelledBidMint.publicKey,
-            ownerTokenAccount: cancelledBidOwnerTokenAccount,
-            winnerTokenAccount: cancelledBidOwnerTokenAccount, // Dummy account
-            owner: owner.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .remainingAccounts([])
-          .signers([owner])
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(lamports)))
+          .signers([bidder])
+          .rpc();
+      }
+
+      let auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.endTs.toString()).to.equal(config.endTs.addn(60).toString());
+
+      // Lowering a trailing bid changes nothing at the top, so the end time stays put
+      await program.methods
+        .placeBid(new anchor.BN(1_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(1_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.endTs.toString()).to.equal(config.endTs.addn(60).toString());
+    });
+
+    it("Fails to create auction with a hard cap before the end time", async () => {
+      const badCapMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, badCapMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.extensionWindowSecs = new anchor.BN(5);
+      config.extensionSecs = new anchor.BN(5);
+      config.maxEndTs = config.endTs.subn(1);
+
+      try {
+        await program.methods
+          .createAuction("Bad Cap", "BCAP", "https://example.com/badcap", config, NO_ROYALTIES)
+          .accounts({
+            auction: accounts.auctionPda,
+            mint: badCapMint.publicKey,
+            metadata: accounts.metadataPda,
+            vault: accounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, badCapMint])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidExtensionConfig");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidExtensionConfig");
+      }
+    });
+  });
+
+  describe("Reserve Price", () => {
+    it("Refunds everyone and keeps the token when a public reserve is not met", async () => {
+      const reserveMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, reserveMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.reserve = { public: { lamports: new anchor.BN(10_000_000) } };
+
+      await program.methods
+        .createAuction("Reserve Test", "RSRV", "https://example.com/reserve", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: reserveMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, reserveMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.reserveLamports.toNumber()).to.equal(10_000_000);
+
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+      const bidRent = await accountRent(provider.connection, bidPda(program.programId, accounts.auctionPda, bidder1.publicKey));
+      const bidder1BalanceBefore = await provider.connection.getBalance(bidder1.publicKey);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: reserveMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      const concludedAuction = await readAuction(provider.connection, accounts.auctionPda);
+      expect(concludedAuction.outcome).to.equal(AUCTION_OUTCOME_REFUND_ALL);
+
+      // The top bid did not win, so it is refunded like any other
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [bidder1.publicKey]);
+
+      // Top bidder was refunded in full, including the bid account rent
+      const bidder1BalanceAfter = await provider.connection.getBalance(bidder1.publicKey);
+      expect(bidder1BalanceAfter - bidder1BalanceBefore).to.equal(2_000_000 + bidRent);
+
+      // Token stayed with the owner
+      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(accounts.ownerTokenAccount);
+      expect(ownerTokenBalance.value.amount).to.equal("1");
+
+      // Auction was closed, its record shows it went unsold
+      const closedAuction = await provider.connection.getAccountInfo(accounts.auctionPda);
+      expect(closedAuction).to.be.null;
+      const record = await program.account.auctionRecord.fetch(recordPda(program.programId, accounts.auctionPda));
+      expect(record.winner.toString()).to.equal(PublicKey.default.toString());
+      expect(record.price.toNumber()).to.equal(0);
+    });
+
+    it("Sells the token once a hidden reserve is revealed and met", async () => {
+      const hiddenMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, hiddenMint.publicKey, owner.publicKey);
+      const reserveLamports = new anchor.BN(3_000_000);
+      const salt = Keypair.generate().publicKey.toBytes();
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.reserve = { hidden: { commitment: saltedCommitment(reserveLamports, salt) } };
+
+      await program.methods
+        .createAuction("Hidden Reserve Test", "HRSV", "https://example.com/hidden", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: hiddenMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, hiddenMint])
+        .rpc();
+
+      // The reserve amount itself is never stored on-chain
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.reserveLamports.toNumber()).to.equal(0);
+
+      await program.methods
+        .placeBid(new anchor.BN(5_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(5_000_000)))
+        .signers([bidder2])
+        .rpc();
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(hiddenMint.publicKey, bidder2.publicKey);
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          owner.publicKey,
+          winnerTokenAccount,
+          bidder2.publicKey,
+          hiddenMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx, [owner]);
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      const concludeAccounts = {
+        auction: accounts.auctionPda,
+        tokenMint: hiddenMint.publicKey,
+        vault: accounts.vault,
+        ownerTokenAccount: accounts.ownerTokenAccount,
+        owner: owner.publicKey,
+        cranker: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      // A reveal that does not match the commitment is rejected
+      try {
+        await program.methods
+          .concludeAuction({ lamports: new anchor.BN(1), salt: Array.from(salt) })
+          .accountsPartial(concludeAccounts)
+          .signers([owner])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidReserveReveal");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidReserveReveal");
+      }
+
+      await program.methods
+        .concludeAuction({ lamports: reserveLamports, salt: Array.from(salt) })
+        .accountsPartial(concludeAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: hiddenMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
+      expect(winnerTokenBalance.value.amount).to.equal("1");
+    });
+  });
+
+  describe("Minimum Bid Increment", () => {
+    it("Rejects new and raised bids that do not beat the top bid by the increment", async () => {
+      const incrementMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, incrementMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, MAX_BIDS_AUCTION_SECS);
+      // Required step is the larger of 1_000_000 lamports and 10% of the top bid
+      config.minIncrementLamports = new anchor.BN(1_000_000);
+      config.minIncrementBps = 1_000;
+
+      await program.methods
+        .createAuction("Increment Test", "INCR", "https://example.com/increment", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: incrementMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, incrementMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.minIncrementLamports.toNumber()).to.equal(1_000_000);
+      expect(auctionAccount.minIncrementBps).to.equal(1_000);
+
+      // Any first bid is accepted
+      await program.methods
+        .placeBid(new anchor.BN(15_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(15_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      // 10% of 15_000_000 is 1_500_000, so 16_000_000 is not enough
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(16_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(16_000_000)))
+          .signers([bidder2])
+          .rpc();
+
+        expect.fail("Should have failed with BidIncrementTooLow");
+      } catch (error) {
+        expect(error.toString()).to.include("BidIncrementTooLow");
+      }
+
+      await program.methods
+        .placeBid(new anchor.BN(16_500_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(16_500_000)))
+        .signers([bidder2])
+        .rpc();
+
+      // Raising an existing bid must also clear the step (16_500_000 + 1_650_000)
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(18_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(18_000_000)))
+          .signers([bidder1])
+          .rpc();
+
+        expect.fail("Should have failed with BidIncrementTooLow");
+      } catch (error) {
+        expect(error.toString()).to.include("BidIncrementTooLow");
+      }
+
+      await program.methods
+        .placeBid(new anchor.BN(18_150_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(18_150_000)))
+        .signers([bidder1])
+        .rpc();
+
+      const updatedAuction = await readAuction(provider.connection, accounts.auctionPda);
+      expect(updatedAuction.bidCount).to.equal(2);
+    });
+
+    it("Rejects bids that do not beat the top bid when no increment is configured", async () => {
+      const defaultMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, defaultMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      await program.methods
+        .createAuction("Default Increment Test", "DINC", "https://example.com/dinc", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: defaultMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, defaultMint])
+        .rpc();
+
+      const placeDefaultBid = async (bidder: Keypair, lamports: number) =>
+        program.methods
+          .placeBid(new anchor.BN(lamports))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(lamports)))
+          .signers([bidder])
+          .rpc();
+
+      await placeDefaultBid(bidder1, 5_000_000);
+
+      // Bids below or equal to the top bid are rejected even without increment rules
+      for (const lamports of [4_000_000, 5_000_000]) {
+        try {
+          await placeDefaultBid(bidder2, lamports);
+          expect.fail("Should have failed with BidIncrementTooLow");
+        } catch (error) {
+          expect(error.toString()).to.include("BidIncrementTooLow");
+        }
+      }
+
+      // One lamport above the top bid is enough
+      await placeDefaultBid(bidder2, 5_000_001);
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder2.publicKey.toString());
+      expect(auctionAccount.topLamports.toNumber()).to.equal(5_000_001);
+    });
+  });
+
+  describe("Buy It Now", () => {
+    it("Settles immediately: token to buyer, price to owner, bids claim refunds", async () => {
+      const buyNowMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, buyNowMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, MAX_BIDS_AUCTION_SECS);
+      config.buyNowLamports = new anchor.BN(20_000_000);
+
+      await program.methods
+        .createAuction("Buy Now Test", "BUYN", "https://example.com/buynow", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: buyNowMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, buyNowMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.buyNowLamports.toNumber()).to.equal(20_000_000);
+
+      await program.methods
+        .placeBid(new anchor.BN(5_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(5_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      const buyerTokenAccount = await getAssociatedTokenAddress(buyNowMint.publicKey, bidder3.publicKey);
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+      const bidRent = await accountRent(provider.connection, bidPda(program.programId, accounts.auctionPda, bidder1.publicKey));
+      const bidder1BalanceBefore = await provider.connection.getBalance(bidder1.publicKey);
+
+      await program.methods
+        .buyNow()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: buyNowMint.publicKey,
+          vault: accounts.vault,
+          buyerTokenAccount: buyerTokenAccount,
+          owner: owner.publicKey,
+          buyer: bidder3.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder3])
+        .rpc();
+
+      // Bidding is over once the token is sold
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(6_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(6_000_000)))
+          .signers([bidder1])
+          .rpc();
+
+        expect.fail("Should have failed with AuctionConcluded");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionConcluded");
+      }
+
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [bidder1.publicKey]);
+
+      // Owner received the buy-it-now price plus auction and vault rent, without paying fees
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      expect(ownerBalanceAfter - ownerBalanceBefore).to.equal(20_000_000 + auctionRent + vaultRent);
+
+      // Existing bidder was refunded, including the bid account rent
+      const bidder1BalanceAfter = await provider.connection.getBalance(bidder1.publicKey);
+      expect(bidder1BalanceAfter - bidder1BalanceBefore).to.equal(5_000_000 + bidRent);
+
+      // Buyer received the token
+      const buyerTokenBalance = await provider.connection.getTokenAccountBalance(buyerTokenAccount);
+      expect(buyerTokenBalance.value.amount).to.equal("1");
+
+      // Auction was closed, the sale is on record
+      const closedAuction = await provider.connection.getAccountInfo(accounts.auctionPda);
+      expect(closedAuction).to.be.null;
+      const record = await program.account.auctionRecord.fetch(recordPda(program.programId, accounts.auctionPda));
+      expect(record.winner.toString()).to.equal(bidder3.publicKey.toString());
+      expect(record.price.toNumber()).to.equal(20_000_000);
+    });
+
+    it("Fails to buy now once a bid has reached the buy-it-now price", async () => {
+      const outbidMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, outbidMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.buyNowLamports = new anchor.BN(4_000_000);
+
+      await program.methods
+        .createAuction("Buy Now Outbid", "BNOB", "https://example.com/buynowoutbid", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: outbidMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, outbidMint])
+        .rpc();
+
+      await program.methods
+        .placeBid(new anchor.BN(5_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(5_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      try {
+        await program.methods
+          .buyNow()
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            tokenMint: outbidMint.publicKey,
+            vault: accounts.vault,
+            buyerTokenAccount: await getAssociatedTokenAddress(outbidMint.publicKey, bidder3.publicKey),
+            owner: owner.publicKey,
+            buyer: bidder3.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([bidder3])
+          .rpc();
+
+        expect.fail("Should have failed with BuyNowOutbid");
+      } catch (error) {
+        expect(error.toString()).to.include("BuyNowOutbid");
+      }
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.outcome).to.equal(AUCTION_OUTCOME_PENDING);
+      expect(auctionAccount.topBidder.equals(bidder1.publicKey)).to.be.true;
+    });
+
+    it("Fails to buy now when no buy-it-now price is set", async () => {
+      const noBuyNowMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, noBuyNowMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      await program.methods
+        .createAuction("No Buy Now", "NOBN", "https://example.com/nobuynow", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: noBuyNowMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, noBuyNowMint])
+        .rpc();
+
+      try {
+        await program.methods
+          .buyNow()
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            tokenMint: noBuyNowMint.publicKey,
+            vault: accounts.vault,
+            buyerTokenAccount: await getAssociatedTokenAddress(noBuyNowMint.publicKey, bidder3.publicKey),
+            owner: owner.publicKey,
+            buyer: bidder3.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([bidder3])
+          .rpc();
+
+        expect.fail("Should have failed with BuyNowDisabled");
+      } catch (error) {
+        expect(error.toString()).to.include("BuyNowDisabled");
+      }
+    });
+  });
+
+  describe("Dutch Auction", () => {
+    it("Sells to the first buyer accepting the decaying price", async () => {
+      const dutchMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, dutchMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, MAX_BIDS_AUCTION_SECS);
+      config.kind = {
+        dutch: {
+          startPrice: new anchor.BN(50_000_000),
+          floorPrice: new anchor.BN(10_000_000),
+          stepSecs: new anchor.BN(0),
+        },
+      };
+
+      await program.methods
+        .createAuction("Dutch Test", "DUTCH", "https://example.com/dutch", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: dutchMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, dutchMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.kind).to.equal(1);
+      expect(auctionAccount.dutchStartPrice.toNumber()).to.equal(50_000_000);
+      expect(auctionAccount.dutchFloorPrice.toNumber()).to.equal(10_000_000);
+
+      // Dutch auctions have no bid table
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(20_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(20_000_000)))
+          .signers([bidder1])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidAuctionKind");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAuctionKind");
+      }
+
+      const buyerTokenAccount = await getAssociatedTokenAddress(dutchMint.publicKey, bidder2.publicKey);
+      const acceptAccounts = {
+        auction: accounts.auctionPda,
+        tokenMint: dutchMint.publicKey,
+        vault: accounts.vault,
+        buyerTokenAccount: buyerTokenAccount,
+        owner: owner.publicKey,
+        buyer: bidder2.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      };
+
+      // The price never drops below the floor, so a lower limit is rejected
+      try {
+        await program.methods
+          .acceptPrice(new anchor.BN(5_000_000))
+          .accountsPartial(acceptAccounts)
+          .signers([bidder2])
+          .rpc();
+
+        expect.fail("Should have failed with PriceAboveLimit");
+      } catch (error) {
+        expect(error.toString()).to.include("PriceAboveLimit");
+      }
+
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+
+      await program.methods
+        .acceptPrice(new anchor.BN(50_000_000))
+        .accountsPartial(acceptAccounts)
+        .signers([bidder2])
+        .rpc();
+
+      // Owner received a price between the floor and the start price
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      const pricePaid = ownerBalanceAfter - ownerBalanceBefore - auctionRent - vaultRent;
+      expect(pricePaid).to.be.greaterThanOrEqual(10_000_000);
+      expect(pricePaid).to.be.lessThanOrEqual(50_000_000);
+
+      // Buyer received the token
+      const buyerTokenBalance = await provider.connection.getTokenAccountBalance(buyerTokenAccount);
+      expect(buyerTokenBalance.value.amount).to.equal("1");
+
+      // Auction was closed, the sale is on record at the price paid
+      const closedAuction = await provider.connection.getAccountInfo(accounts.auctionPda);
+      expect(closedAuction).to.be.null;
+      const record = await program.account.auctionRecord.fetch(recordPda(program.programId, accounts.auctionPda));
+      expect(record.winner.toString()).to.equal(bidder2.publicKey.toString());
+      expect(record.price.toNumber()).to.equal(pricePaid);
+    });
+
+    it("Holds the floor price for the final step so it can be accepted", async () => {
+      const floorMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, floorMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, 2 * SHORT_AUCTION_SECS);
+      // Two steps: the start price for the first half, the floor for the second
+      config.kind = {
+        dutch: {
+          startPrice: new anchor.BN(50_000_000),
+          floorPrice: new anchor.BN(10_000_000),
+          stepSecs: new anchor.BN(SHORT_AUCTION_SECS),
+        },
+      };
+
+      await program.methods
+        .createAuction("Dutch Floor", "DFLR", "https://example.com/dutchfloor", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: floorMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, floorMint])
+        .rpc();
+
+      while ((await clusterTime(provider.connection)) <= config.startTs.toNumber() + SHORT_AUCTION_SECS) {
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+      }
+
+      const buyerTokenAccount = await getAssociatedTokenAddress(floorMint.publicKey, bidder2.publicKey);
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+
+      await program.methods
+        .acceptPrice(new anchor.BN(10_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: floorMint.publicKey,
+          vault: accounts.vault,
+          buyerTokenAccount: buyerTokenAccount,
+          owner: owner.publicKey,
+          buyer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      // Owner received exactly the floor price
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      expect(ownerBalanceAfter - ownerBalanceBefore).to.equal(10_000_000 + auctionRent + vaultRent);
+
+      const buyerTokenBalance = await provider.connection.getTokenAccountBalance(buyerTokenAccount);
+      expect(buyerTokenBalance.value.amount).to.equal("1");
+    });
+
+    it("Fails to create a Dutch auction whose floor is not below the start price", async () => {
+      const badDutchMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, badDutchMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.kind = {
+        dutch: {
+          startPrice: new anchor.BN(10_000_000),
+          floorPrice: new anchor.BN(10_000_000),
+          stepSecs: new anchor.BN(5),
+        },
+      };
+
+      try {
+        await program.methods
+          .createAuction("Bad Dutch", "BDUT", "https://example.com/baddutch", config, NO_ROYALTIES)
+          .accounts({
+            auction: accounts.auctionPda,
+            mint: badDutchMint.publicKey,
+            metadata: accounts.metadataPda,
+            vault: accounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, badDutchMint])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidDutchConfig");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidDutchConfig");
+      }
+    });
+  });
+
+  describe("Sealed Bid Auction", () => {
+    it("Settles to the highest revealed bid and forfeits unrevealed deposits", async () => {
+      const sealedMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, sealedMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.kind = {
+        sealed: {
+          revealEndTs: config.endTs.add(new anchor.BN(SHORT_AUCTION_SECS)),
+          forfeitUnrevealed: true,
+        },
+      };
+
+      await program.methods
+        .createAuction("Sealed Test", "SEAL", "https://example.com/sealed", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: sealedMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, sealedMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.kind).to.equal(2);
+      expect(auctionAccount.forfeitUnrevealed).to.equal(true);
+
+      // Each bidder commits to an amount and over-deposits to hide it
+      const sealedBids = [
+        { bidder: bidder1, lamports: new anchor.BN(5_000_000), deposit: new anchor.BN(8_000_000) },
+        { bidder: bidder2, lamports: new anchor.BN(7_000_000), deposit: new anchor.BN(7_000_000) },
+        { bidder: bidder3, lamports: new anchor.BN(9_000_000), deposit: new anchor.BN(10_000_000) },
+      ].map((bid) => ({ ...bid, salt: Keypair.generate().publicKey.toBytes() }));
+
+      for (const bid of sealedBids) {
+        await program.methods
+          .commitBid(saltedCommitment(bid.lamports, bid.salt), bid.deposit)
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bid.bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([bid.bidder])
+          .rpc();
+      }
+
+      // Sealed auctions do not accept open bids
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(20_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(20_000_000)))
+          .signers([bidder1])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidAuctionKind");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAuctionKind");
+      }
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      // A reveal that does not match the commitment is rejected
+      try {
+        await program.methods
+          .revealBid(new anchor.BN(6_000_000), Array.from(sealedBids[0].salt))
+          .accountsPartial({ auction: accounts.auctionPda, bidder: bidder1.publicKey })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(6_000_000)))
+          .signers([bidder1])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidBidReveal");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidBidReveal");
+      }
+
+      // bidder1 and bidder2 reveal, bidder3 never does
+      const bidder1BalanceBefore = await provider.connection.getBalance(bidder1.publicKey);
+      for (const bid of sealedBids.slice(0, 2)) {
+        await program.methods
+          .revealBid(bid.lamports, Array.from(bid.salt))
+          .accountsPartial({ auction: accounts.auctionPda, bidder: bid.bidder.publicKey })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bid.bidder.publicKey, bid.lamports))
+          .signers([bid.bidder])
+          .rpc();
+      }
+
+      // Excess deposit above the revealed amount was refunded (bidder1 pays its own fee)
+      const bidder1BalanceAfterReveal = await provider.connection.getBalance(bidder1.publicKey);
+      expect(bidder1BalanceAfterReveal - bidder1BalanceBefore).to.be.closeTo(3_000_000, 10_000);
+
+      const revealedAuction = await readAuction(provider.connection, accounts.auctionPda);
+      const revealedBy = revealedAuction.bids.filter((b) => b.revealed).map((b) => b.bidder.toBase58());
+      expect(revealedBy).to.have.members([bidder1.publicKey.toBase58(), bidder2.publicKey.toBase58()]);
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(sealedMint.publicKey, bidder2.publicKey);
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          owner.publicKey,
+          winnerTokenAccount,
+          bidder2.publicKey,
+          sealedMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx, [owner]);
+
+      const concludeAccounts = {
+        auction: accounts.auctionPda,
+        tokenMint: sealedMint.publicKey,
+        vault: accounts.vault,
+        ownerTokenAccount: accounts.ownerTokenAccount,
+        owner: owner.publicKey,
+        cranker: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      // Settlement waits for the reveal phase to close
+      try {
+        await program.methods
+          .concludeAuction(null)
+          .accountsPartial(concludeAccounts)
+          .signers([owner])
+          .rpc();
+
+        expect.fail("Should have failed with AuctionNotEnded");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionNotEnded");
+      }
+
+      await waitForRevealEnd(provider.connection, accounts.auctionPda);
+
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+      const bidRent = await accountRent(provider.connection, bidPda(program.programId, accounts.auctionPda, bidder1.publicKey));
+      const bidder1BalanceBeforeConclude = await provider.connection.getBalance(bidder1.publicKey);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial(concludeAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: sealedMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      // Unrevealed deposits are forfeited when their refund is claimed
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [
+        bidder1.publicKey,
+        bidder3.publicKey,
+      ]);
+
+      // bidder2 won with the highest revealed bid, bidder3's deposit was forfeited to the owner
+      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
+      expect(winnerTokenBalance.value.amount).to.equal("1");
+
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      const expectedIncrease = 7_000_000 + 10_000_000 + auctionRent + vaultRent;
+      expect(ownerBalanceAfter - ownerBalanceBefore).to.be.closeTo(expectedIncrease, 10_000);
+
+      const bidder1BalanceAfterConclude = await provider.connection.getBalance(bidder1.publicKey);
+      expect(bidder1BalanceAfterConclude - bidder1BalanceBeforeConclude).to.equal(5_000_000 + bidRent);
+    });
+
+    it("Fails to create a sealed auction whose reveal phase ends before bidding", async () => {
+      const badSealedMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, badSealedMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.kind = { sealed: { revealEndTs: config.endTs, forfeitUnrevealed: false } };
+
+      try {
+        await program.methods
+          .createAuction("Bad Sealed", "BSEL", "https://example.com/badsealed", config, NO_ROYALTIES)
+          .accounts({
+            auction: accounts.auctionPda,
+            mint: badSealedMint.publicKey,
+            metadata: accounts.metadataPda,
+            vault: accounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, badSealedMint])
+          .rpc();
+
+        expect.fail("Should have failed with InvalidSealedConfig");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidSealedConfig");
+      }
+    });
+  });
+
+  describe("Second-Price Settlement", () => {
+    it("Charges the winner the second-highest bid and refunds the difference", async () => {
+      const vickreyMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, vickreyMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.settlement = { secondPrice: {} };
+
+      await program.methods
+        .createAuction("Vickrey Test", "VICK", "https://example.com/vickrey", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: vickreyMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, vickreyMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.settlementMode).to.equal(1);
+
+      for (const [bidder, lamports] of [[bidder1, 4_000_000], [bidder2, 6_000_000]] as [Keypair, number][]) {
+        await program.methods
+          .placeBid(new anchor.BN(lamports))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(lamports)))
+          .signers([bidder])
+          .rpc();
+      }
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(vickreyMint.publicKey, bidder2.publicKey);
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          owner.publicKey,
+          winnerTokenAccount,
+          bidder2.publicKey,
+          vickreyMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx, [owner]);
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+      const bidRent = await accountRent(provider.connection, bidPda(program.programId, accounts.auctionPda, bidder1.publicKey));
+      const bidder1BalanceBefore = await provider.connection.getBalance(bidder1.publicKey);
+      const bidder2BalanceBefore = await provider.connection.getBalance(bidder2.publicKey);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: vickreyMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      // The winner's bid account is closed returning the difference, the loser's refunded in full
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: vickreyMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [bidder1.publicKey]);
+
+      // Owner received the second-highest bid, less the auction record rent of their conclusion
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      const expectedIncrease = 4_000_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(ownerBalanceAfter - ownerBalanceBefore).to.be.closeTo(expectedIncrease, 10_000);
+
+      // Winner got the difference back, loser got a full refund
+      const bidder2BalanceAfter = await provider.connection.getBalance(bidder2.publicKey);
+      expect(bidder2BalanceAfter - bidder2BalanceBefore).to.equal(2_000_000 + bidRent);
+      const bidder1BalanceAfter = await provider.connection.getBalance(bidder1.publicKey);
+      expect(bidder1BalanceAfter - bidder1BalanceBefore).to.equal(4_000_000 + bidRent);
+
+      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
+      expect(winnerTokenBalance.value.amount).to.equal("1");
+    });
+  });
+
+  describe("Keeper Bounty", () => {
+    it("Lets anyone conclude an ended auction and pays them the bounty", async () => {
+      const keeper = Keypair.generate();
+      await airdrop(provider.connection, keeper.publicKey);
+
+      const bountyMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, bountyMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.keeperBountyBps = 200;
+
+      await program.methods
+        .createAuction("Bounty Test", "BNTY", "https://example.com/bounty", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: bountyMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, bountyMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.keeperBountyBps).to.equal(200);
+
+      for (const [bidder, lamports] of [[bidder1, 4_000_000], [bidder2, 6_000_000]] as [Keypair, number][]) {
+        await program.methods
+          .placeBid(new anchor.BN(lamports))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(lamports)))
+          .signers([bidder])
+          .rpc();
+      }
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      // The bounty is paid out of the winning bid, which must be passed
+      try {
+        await program.methods
+          .concludeAuction(null)
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            winningBid: null,
+            tokenMint: bountyMint.publicKey,
+            vault: accounts.vault,
+            ownerTokenAccount: accounts.ownerTokenAccount,
+            owner: owner.publicKey,
+            cranker: keeper.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([keeper])
+          .rpc();
+        expect.fail("Should have failed with WinningBidRequired");
+      } catch (error) {
+        expect(error.toString()).to.include("WinningBidRequired");
+      }
+
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+      const keeperBalanceBefore = await provider.connection.getBalance(keeper.publicKey);
+
+      // The keeper concludes without the owner's signature
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          winningBid: bidPda(program.programId, accounts.auctionPda, bidder2.publicKey),
+          tokenMint: bountyMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: keeper.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+
+      // 2% of the 6_000_000 lamport price goes to the keeper (who pays the auction record's rent),
+      // the rest is owed to the owner
+      const keeperBalanceAfter = await provider.connection.getBalance(keeper.publicKey);
+      expect(keeperBalanceAfter - keeperBalanceBefore).to.be.closeTo(
+        120_000 - (await recordRent(provider.connection)),
+        10_000
+      );
+
+      // The sale stays on record for provenance
+      const record = await program.account.auctionRecord.fetch(recordPda(program.programId, accounts.auctionPda));
+      expect(record.auction.toString()).to.equal(accounts.auctionPda.toString());
+      expect(record.tokenMint.toString()).to.equal(bountyMint.publicKey.toString());
+      expect(record.seller.toString()).to.equal(owner.publicKey.toString());
+      expect(record.winner.toString()).to.equal(bidder2.publicKey.toString());
+      expect(record.price.toNumber()).to.equal(6_000_000);
+
+      const concludedAuction = await readAuction(provider.connection, accounts.auctionPda);
+      expect(concludedAuction.outcome).to.equal(AUCTION_OUTCOME_BID_WON);
+      expect(concludedAuction.saleLamports.toString()).to.equal("5880000");
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(bountyMint.publicKey, bidder2.publicKey);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: bountyMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [bidder1.publicKey]);
+
+      // The owner signed nothing and received the price less the bounty
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      expect(ownerBalanceAfter - ownerBalanceBefore).to.equal(5_880_000 + auctionRent + vaultRent);
+
+      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
+      expect(winnerTokenBalance.value.amount).to.equal("1");
+    });
+
+    it("Fails to create an auction with a keeper bounty above the maximum", async () => {
+      const bountyMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, bountyMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.keeperBountyBps = 501;
+
+      try {
+        await program.methods
+          .createAuction("Bounty Test", "BNTY", "https://example.com/bounty", config, NO_ROYALTIES)
+          .accounts({
+            auction: accounts.auctionPda,
+            mint: bountyMint.publicKey,
+            metadata: accounts.metadataPda,
+            vault: accounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, bountyMint])
+          .rpc();
+        expect.fail("Should have failed with InvalidKeeperBounty");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidKeeperBounty");
+      }
+    });
+  });
+
+  describe("Grace Period", () => {
+    it("Refunds every bid once a hidden reserve misses its reveal deadline", async () => {
+      // One auction without bids and one with a hidden reserve its owner never reveals
+      const emptyMint = Keypair.generate();
+      const emptyAccounts = await deriveAuctionAccounts(program.programId, emptyMint.publicKey, owner.publicKey);
+      const graceMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, graceMint.publicKey, owner.publicKey);
+
+      for (const [mint, auctionAccounts, reserve] of [
+        [emptyMint, emptyAccounts, { none: {} }],
+        [
+          graceMint,
+          accounts,
+          { hidden: { commitment: saltedCommitment(new anchor.BN(3_000_000), Keypair.generate().publicKey.toBytes()) } },
+        ],
+      ] as [Keypair, typeof accounts, any][]) {
+        const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+        config.reserve = reserve;
+        await program.methods
+          .createAuction("Grace Test", "GRACE", "https://example.com/grace", config, NO_ROYALTIES)
+          .accounts({
+            auction: auctionAccounts.auctionPda,
+            mint: mint.publicKey,
+            metadata: auctionAccounts.metadataPda,
+            vault: auctionAccounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, mint])
+          .rpc();
+      }
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.gracePeriodSecs.toNumber()).to.equal(MIN_GRACE_PERIOD_SECS);
+
+      for (const [bidder, lamports] of [[bidder1, 4_000_000], [bidder2, 6_000_000]] as [Keypair, number][]) {
+        await program.methods
+          .placeBid(new anchor.BN(lamports))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(lamports)))
+          .signers([bidder])
+          .rpc();
+      }
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      const concludeWithoutReveal = (caller: Keypair) =>
+        program.methods
+          .concludeAuction(null)
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            tokenMint: graceMint.publicKey,
+            vault: accounts.vault,
+            ownerTokenAccount: accounts.ownerTokenAccount,
+            owner: owner.publicKey,
+            cranker: caller.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([caller])
+          .rpc();
+      const forceCancel = (mint: Keypair, auctionAccounts: typeof accounts) =>
+        program.methods
+          .forceCancelAuction()
+          .accountsPartial({
+            auction: auctionAccounts.auctionPda,
+            tokenMint: mint.publicKey,
+            vault: auctionAccounts.vault,
+            ownerTokenAccount: auctionAccounts.ownerTokenAccount,
+            owner: owner.publicKey,
+            caller: bidder1.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([bidder1])
+          .rpc();
+
+      // The owner still has the grace period to reveal the reserve
+      try {
+        await concludeWithoutReveal(bidder1);
+        expect.fail("Should have failed with InvalidReserveReveal");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidReserveReveal");
+      }
+
+      // ...or to conclude an auction without bids
+      try {
+        await forceCancel(emptyMint, emptyAccounts);
+        expect.fail("Should have failed with GracePeriodNotElapsed");
+      } catch (error) {
+        expect(error.toString()).to.include("GracePeriodNotElapsed");
+      }
+
+      await waitForGracePeriod(provider.connection, emptyAccounts.auctionPda);
+      await waitForGracePeriod(provider.connection, accounts.auctionPda);
+
+      // Bids cannot be force-cancelled, so the owner cannot take the item back
+      try {
+        await forceCancel(graceMint, accounts);
+        expect.fail("Should have failed with AuctionConcludable");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionConcludable");
+      }
+
+      // An auction without bids is force-cancelled and closed
+      await forceCancel(emptyMint, emptyAccounts);
+      const emptyOwnerBalance = await provider.connection.getTokenAccountBalance(emptyAccounts.ownerTokenAccount);
+      expect(emptyOwnerBalance.value.amount).to.equal("1");
+      expect(await provider.connection.getAccountInfo(emptyAccounts.auctionPda)).to.be.null;
+
+      // Past the deadline a bidder concludes without the reveal: the reserve counts as not met
+      await concludeWithoutReveal(bidder1);
+
+      const concludedAuction = await readAuction(provider.connection, accounts.auctionPda);
+      expect(concludedAuction.outcome).to.equal(AUCTION_OUTCOME_REFUND_ALL);
+
+      // The token went back to the owner and the vault is closed
+      const ownerTokenBalance = await provider.connection.getTokenAccountBalance(accounts.ownerTokenAccount);
+      expect(ownerTokenBalance.value.amount).to.equal("1");
+      expect(await provider.connection.getAccountInfo(accounts.vault)).to.be.null;
+
+      // Both bids, including the leading one, are refunded in full
+      const bidRent = await accountRent(provider.connection, bidPda(program.programId, accounts.auctionPda, bidder2.publicKey));
+      const bidder2BalanceBefore = await provider.connection.getBalance(bidder2.publicKey);
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [bidder1.publicKey, bidder2.publicKey]);
+      const bidder2BalanceAfter = await provider.connection.getBalance(bidder2.publicKey);
+      expect(bidder2BalanceAfter - bidder2BalanceBefore).to.equal(6_000_000 + bidRent);
+
+      // The last refund closed the auction
+      expect(await provider.connection.getAccountInfo(accounts.auctionPda)).to.be.null;
+    });
+  });
+
+  describe("Existing Mint", () => {
+    it("Auctions an amount of an existing token and lets the winner relist it", async () => {
+      // The owner already holds 1_000_000 base units of a 6-decimal token
+      const existingMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const ownerAta = await createAssociatedTokenAccount(provider.connection, owner, existingMint, owner.publicKey);
+      await mintTo(provider.connection, owner, existingMint, ownerAta, owner, 1_000_000);
+
+      const accounts = await deriveAuctionAccounts(program.programId, existingMint, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      try {
+        await program.methods
+          .createAuctionForExistingMint(new anchor.BN(0), config)
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            tokenMint: existingMint,
+            ownerTokenAccount: ownerAta,
+            vault: accounts.vault,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([owner])
+          .rpc();
+        expect.fail("Should have failed with InvalidTokenAmount");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidTokenAmount");
+      }
+
+      await program.methods
+        .createAuctionForExistingMint(new anchor.BN(250_000), config)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: existingMint,
+          ownerTokenAccount: ownerAta,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.tokenAmount.toNumber()).to.equal(250_000);
+      expect(auctionAccount.minted).to.be.false;
+      expect((await provider.connection.getTokenAccountBalance(accounts.vault)).value.amount).to.equal("250000");
+      expect((await provider.connection.getTokenAccountBalance(ownerAta)).value.amount).to.equal("750000");
+
+      await program.methods
+        .placeBid(new anchor.BN(3_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(3_000_000)))
+        .signers([bidder2])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: existingMint,
+          vault: accounts.vault,
+          ownerTokenAccount: ownerAta,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      const winnerAta = await getAssociatedTokenAddress(existingMint, bidder2.publicKey);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: existingMint,
+          vault: accounts.vault,
+          winnerTokenAccount: winnerAta,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      expect((await provider.connection.getTokenAccountBalance(winnerAta)).value.amount).to.equal("250000");
+      expect(await provider.connection.getAccountInfo(accounts.vault)).to.be.null;
+
+      // The winner relists the same mint as its second auction
+      const relist = await deriveAuctionAccounts(program.programId, existingMint, bidder2.publicKey, 1);
+      const relistConfig = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      await program.methods
+        .createAuctionForExistingMint(new anchor.BN(250_000), relistConfig)
+        .accountsPartial({
+          auction: relist.auctionPda,
+          tokenMint: existingMint,
+          ownerTokenAccount: winnerAta,
+          vault: relist.vault,
+          owner: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      expect((await provider.connection.getTokenAccountBalance(relist.vault)).value.amount).to.equal("250000");
+      expect((await readAuction(provider.connection, relist.auctionPda)).nonce).to.equal(1);
+
+      // The counter enumerates every auction of the mint, including the closed first one
+      const [counterPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("auction_counter"), existingMint.toBuffer()],
+        program.programId
+      );
+      const counter = await program.account.auctionCounter.fetch(counterPda);
+      expect(counter.count.toNumber()).to.equal(2);
+      expect(counter.tokenMint.equals(existingMint)).to.be.true;
+
+      // Cancelling returns deposited tokens instead of burning them
+      await program.methods
+        .cancelAuction()
+        .accountsPartial({
+          auction: relist.auctionPda,
+          tokenMint: existingMint,
+          vault: relist.vault,
+          ownerTokenAccount: winnerAta,
+          owner: bidder2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      expect((await provider.connection.getTokenAccountBalance(winnerAta)).value.amount).to.equal("250000");
+      expect(await provider.connection.getAccountInfo(relist.vault)).to.be.null;
+      expect(await provider.connection.getAccountInfo(relist.auctionPda)).to.be.null;
+    });
+  });
+
+  describe("Royalties", () => {
+    it("Pays the verified creators their royalty when a minted token is resold", async () => {
+      const royaltyMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, royaltyMint.publicKey, owner.publicKey);
+      const createAccounts = {
+        auction: accounts.auctionPda,
+        mint: royaltyMint.publicKey,
+        metadata: accounts.metadataPda,
+        vault: accounts.vault,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      };
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      // Creator shares must sum to 100
+      try {
+        await program.methods
+          .createAuction("Royalty Test", "RYLT", "https://example.com/royalty", config, {
+            sellerFeeBasisPoints: 1_000,
+            creators: [{ address: owner.publicKey, share: 90 }],
+          })
+          .accounts(createAccounts)
+          .signers([owner, royaltyMint])
+          .rpc();
+        expect.fail("Should have failed with InvalidRoyalties");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidRoyalties");
+      }
+
+      // 10% royalties, all to the owner (verified as the signing update authority)
+      await program.methods
+        .createAuction("Royalty Test", "RYLT", "https://example.com/royalty", config, {
+          sellerFeeBasisPoints: 1_000,
+          creators: [{ address: owner.publicKey, share: 100 }],
+        })
+        .accounts(createAccounts)
+        .signers([owner, royaltyMint])
+        .rpc();
+
+      // The primary sale pays no royalties
+      expect((await readAuction(provider.connection, accounts.auctionPda)).royalties).to.be.false;
+
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: royaltyMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      const collectorAta = await getAssociatedTokenAddress(royaltyMint.publicKey, bidder1.publicKey);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: royaltyMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: collectorAta,
+          owner: owner.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      // The collector resells the token
+      const resale = await deriveAuctionAccounts(program.programId, royaltyMint.publicKey, bidder1.publicKey, 1);
+      const resaleAccounts = {
+        auction: resale.auctionPda,
+        tokenMint: royaltyMint.publicKey,
+        ownerTokenAccount: collectorAta,
+        vault: resale.vault,
+        metadata: accounts.metadataPda,
+        owner: bidder1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      // Buy-it-now would settle without conclusion, skipping the royalties
+      const buyNowConfig = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      buyNowConfig.buyNowLamports = new anchor.BN(5_000_000);
+      try {
+        await program.methods
+          .createAuctionForExistingMint(new anchor.BN(1), buyNowConfig)
+          .accountsPartial(resaleAccounts)
+          .signers([bidder1])
+          .rpc();
+        expect.fail("Should have failed with InvalidRoyaltyConfig");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidRoyaltyConfig");
+      }
+
+      // The royalties are read from the token's metadata, which must be passed
+      // The seller also offers a 5% keeper bounty, which they collect by concluding the resale themselves
+      const resaleConfig = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      resaleConfig.keeperBountyBps = 500;
+      try {
+        await program.methods
+          .createAuctionForExistingMint(new anchor.BN(1), resaleConfig)
+          .accountsPartial({ ...resaleAccounts, metadata: null })
+          .signers([bidder1])
+          .rpc();
+        expect.fail("Should have failed with MetadataRequired");
+      } catch (error) {
+        expect(error.toString()).to.include("MetadataRequired");
+      }
+
+      await program.methods
+        .createAuctionForExistingMint(new anchor.BN(1), resaleConfig)
+        .accountsPartial(resaleAccounts)
+        .signers([bidder1])
+        .rpc();
+
+      // The rate and verified creators are snapshotted at listing
+      const listedResale = await readAuction(provider.connection, resale.auctionPda);
+      expect(listedResale.royalties).to.be.true;
+      expect(listedResale.royaltyBps).to.equal(1_000);
+      expect(listedResale.royaltyCreators.map((creator) => creator.toBase58())).to.deep.equal([
+        owner.publicKey.toBase58(),
+      ]);
+      expect(listedResale.royaltyShares).to.deep.equal([100]);
+
+      await program.methods
+        .placeBid(new anchor.BN(3_000_000))
+        .accountsPartial({
+          auction: resale.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, resale.auctionPda, bidder2.publicKey, new anchor.BN(3_000_000)))
+        .signers([bidder2])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, resale.auctionPda);
+
+      const concludeAccounts = {
+        auction: resale.auctionPda,
+        winningBid: bidPda(program.programId, resale.auctionPda, bidder2.publicKey),
+        tokenMint: royaltyMint.publicKey,
+        vault: resale.vault,
+        ownerTokenAccount: collectorAta,
+        owner: bidder1.publicKey,
+        cranker: bidder1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      // Every verified creator must be passed to receive their share
+      try {
+        await program.methods
+          .concludeAuction(null)
+          .accountsPartial(concludeAccounts)
+          .signers([bidder1])
+          .rpc();
+        expect.fail("Should have failed with CreatorAccountMissing");
+      } catch (error) {
+        expect(error.toString()).to.include("CreatorAccountMissing");
+      }
+
+      const creatorBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial(concludeAccounts)
+        .remainingAccounts([{ pubkey: owner.publicKey, isWritable: true, isSigner: false }])
+        .signers([bidder1])
+        .rpc();
+
+      // 10% of the full 3_000_000 lamport resale goes to the creator, the 150_000 lamport bounty
+      // comes out of the seller's remainder
+      const creatorBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      expect(creatorBalanceAfter - creatorBalanceBefore).to.equal(300_000);
+      const resaleAuction = await readAuction(provider.connection, resale.auctionPda);
+      expect(resaleAuction.saleLamports.toNumber()).to.equal(2_550_000);
+    });
+
+    it("Skips a royalty the creator's wallet cannot hold and still concludes the resale", async () => {
+      // An artist mints and sells a token paying them 10% royalties
+      const artist = Keypair.generate();
+      await airdrop(provider.connection, artist.publicKey);
+      const artworkMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, artworkMint.publicKey, artist.publicKey);
+      await program.methods
+        .createAuction("Artwork", "ART", "https://example.com/artwork", await auctionConfig(provider.connection, SHORT_AUCTION_SECS), {
+          sellerFeeBasisPoints: 1_000,
+          creators: [{ address: artist.publicKey, share: 100 }],
+        })
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: artworkMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: artist.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([artist, artworkMint])
+        .rpc();
+
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: artworkMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: artist.publicKey,
+          cranker: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      const collectorAta = await getAssociatedTokenAddress(artworkMint.publicKey, bidder1.publicKey);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: artworkMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: collectorAta,
+          owner: artist.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      // The collector resells it
+      const resale = await deriveAuctionAccounts(program.programId, artworkMint.publicKey, bidder1.publicKey, 1);
+      await program.methods
+        .createAuctionForExistingMint(new anchor.BN(1), await auctionConfig(provider.connection, SHORT_AUCTION_SECS))
+        .accountsPartial({
+          auction: resale.auctionPda,
+          tokenMint: artworkMint.publicKey,
+          ownerTokenAccount: collectorAta,
+          vault: resale.vault,
+          metadata: accounts.metadataPda,
+          owner: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      await program.methods
+        .placeBid(new anchor.BN(3_000_000))
+        .accountsPartial({
+          auction: resale.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, resale.auctionPda, bidder2.publicKey, new anchor.BN(3_000_000)))
+        .signers([bidder2])
+        .rpc();
+
+      // Meanwhile the artist empties their wallet, which can no longer hold a 300_000 lamport royalty
+      const drainTx = new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: artist.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: await provider.connection.getBalance(artist.publicKey),
+        })
+      );
+      await provider.sendAndConfirm(drainTx, [artist]);
+      expect(await provider.connection.getBalance(artist.publicKey)).to.equal(0);
+
+      await waitForAuctionEnd(provider.connection, resale.auctionPda);
+
+      // The undeliverable royalty is skipped instead of blocking the conclusion
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: resale.auctionPda,
+          winningBid: bidPda(program.programId, resale.auctionPda, bidder2.publicKey),
+          tokenMint: artworkMint.publicKey,
+          vault: resale.vault,
+          ownerTokenAccount: collectorAta,
+          owner: bidder1.publicKey,
+          cranker: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([{ pubkey: artist.publicKey, isWritable: true, isSigner: false }])
+        .signers([bidder1])
+        .rpc();
+
+      // Its share stays with the seller
+      const resaleAuction = await readAuction(provider.connection, resale.auctionPda);
+      expect(resaleAuction.outcome).to.equal(AUCTION_OUTCOME_BID_WON);
+      expect(resaleAuction.saleLamports.toNumber()).to.equal(3_000_000);
+      expect(await provider.connection.getBalance(artist.publicKey)).to.equal(0);
+    });
+
+    it("Pays a token royalty into a payment account opened for a creator who never held the token", async () => {
+      // An artist mints and sells a token paying them 10% royalties
+      const artist = Keypair.generate();
+      await airdrop(provider.connection, artist.publicKey);
+      const artworkMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, artworkMint.publicKey, artist.publicKey);
+      await program.methods
+        .createAuction("Artwork", "ART", "https://example.com/artwork", await auctionConfig(provider.connection, SHORT_AUCTION_SECS), {
+          sellerFeeBasisPoints: 1_000,
+          creators: [{ address: artist.publicKey, share: 100 }],
+        })
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: artworkMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: artist.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([artist, artworkMint])
+        .rpc();
+
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: artworkMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: artist.publicKey,
+          cranker: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      const collectorAta = await getAssociatedTokenAddress(artworkMint.publicKey, bidder1.publicKey);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: artworkMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: collectorAta,
+          owner: artist.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      // The collector resells it for a payment token the artist has never held
+      const paymentMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const bidderPaymentAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        owner,
+        paymentMint,
+        bidder2.publicKey
+      );
+      await mintTo(provider.connection, owner, paymentMint, bidderPaymentAccount, owner, 10_000_000);
+
+      const resale = await deriveAuctionAccounts(program.programId, artworkMint.publicKey, bidder1.publicKey, 1);
+      await program.methods
+        .createAuctionForExistingMint(new anchor.BN(1), await auctionConfig(provider.connection, SHORT_AUCTION_SECS))
+        .accountsPartial({
+          auction: resale.auctionPda,
+          tokenMint: artworkMint.publicKey,
+          ownerTokenAccount: collectorAta,
+          vault: resale.vault,
+          paymentMint,
+          metadata: accounts.metadataPda,
+          owner: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      const winningBid = bidPda(program.programId, resale.auctionPda, bidder2.publicKey);
+      const winningBidEscrow = await getAssociatedTokenAddress(paymentMint, winningBid, true);
+      await program.methods
+        .placeBid(new anchor.BN(3_000_000))
+        .accountsPartial({
+          auction: resale.auctionPda,
+          bidder: bidder2.publicKey,
+          paymentMint,
+          bidEscrow: winningBidEscrow,
+          bidderPaymentAccount,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, resale.auctionPda, bidder2.publicKey, new anchor.BN(3_000_000)))
+        .signers([bidder2])
+        .rpc();
+
+      const artistPaymentAccount = await getAssociatedTokenAddress(paymentMint, artist.publicKey);
+      expect(await provider.connection.getAccountInfo(artistPaymentAccount)).to.be.null;
+
+      await waitForAuctionEnd(provider.connection, resale.auctionPda);
+
+      // The seller concludes, paying for the artist's payment account rather than skipping the royalty
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: resale.auctionPda,
+          winningBid,
+          paymentMint,
+          winningBidEscrow,
+          tokenMint: artworkMint.publicKey,
+          vault: resale.vault,
+          ownerTokenAccount: collectorAta,
+          owner: bidder1.publicKey,
+          cranker: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: artist.publicKey, isWritable: false, isSigner: false },
+          { pubkey: artistPaymentAccount, isWritable: true, isSigner: false },
+        ])
+        .signers([bidder1])
+        .rpc();
+
+      // 10% of the 3_000_000 token resale goes to the artist, the seller is owed the rest
+      expect((await provider.connection.getTokenAccountBalance(artistPaymentAccount)).value.amount).to.equal(
+        "300000"
+      );
+      const resaleAuction = await readAuction(provider.connection, resale.auctionPda);
+      expect(resaleAuction.saleLamports.toNumber()).to.equal(2_700_000);
+    });
+  });
+
+  describe("Collections", () => {
+    it("Verifies auctioned tokens into the owner's collection", async () => {
+      const collectionMint = Keypair.generate();
+      const collection = await deriveAuctionAccounts(program.programId, collectionMint.publicKey, owner.publicKey);
+
+      await program.methods
+        .createCollection("Spring Drop", "DROP", "https://example.com/drop")
+        .accountsPartial({
+          collectionMint: collectionMint.publicKey,
+          metadata: collection.metadataPda,
+          ownerTokenAccount: collection.ownerTokenAccount,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([owner, collectionMint])
+        .rpc();
+
+      // The seller holds the collection NFT, a sized collection with no items yet
+      expect((await provider.connection.getTokenAccountBalance(collection.ownerTokenAccount)).value.amount).to.equal("1");
+      expect((await readMetadataCollection(provider.connection, collection.metadataPda)).collectionSize).to.equal(0);
+
+      const itemMint = Keypair.generate();
+      const item = await deriveAuctionAccounts(program.programId, itemMint.publicKey, owner.publicKey);
+      const itemAccounts = {
+        auction: item.auctionPda,
+        mint: itemMint.publicKey,
+        metadata: item.metadataPda,
+        vault: item.vault,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+      };
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      // The collection accounts are passed all together or not at all
+      try {
+        await program.methods
+          .createAuction("Drop Item", "DROP", "https://example.com/drop/1", config, NO_ROYALTIES)
+          .accountsPartial({ ...itemAccounts, collectionMint: collectionMint.publicKey })
+          .signers([owner, itemMint])
+          .rpc();
+        expect.fail("Should have failed with CollectionAccountsMissing");
+      } catch (error) {
+        expect(error.toString()).to.include("CollectionAccountsMissing");
+      }
+
+      await program.methods
+        .createAuction("Drop Item", "DROP", "https://example.com/drop/1", config, NO_ROYALTIES)
+        .accountsPartial({
+          ...itemAccounts,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata: collection.metadataPda,
+          collectionMasterEdition: collection.masterEditionPda,
+        })
+        .signers([owner, itemMint])
+        .rpc();
+
+      // The item is a verified member of the collection, which now counts it
+      const itemCollection = (await readMetadataCollection(provider.connection, item.metadataPda)).collection;
+      expect(itemCollection.verified).to.be.true;
+      expect(itemCollection.key.equals(collectionMint.publicKey)).to.be.true;
+      expect((await readMetadataCollection(provider.connection, collection.metadataPda)).collectionSize).to.equal(1);
+
+      // Only the collection's update authority can verify items into it
+      const outsider = Keypair.generate();
+      await airdrop(provider.connection, outsider.publicKey);
+      const outsiderMint = Keypair.generate();
+      const outsiderItem = await deriveAuctionAccounts(program.programId, outsiderMint.publicKey, outsider.publicKey);
+      try {
+        await program.methods
+          .createAuction("Fake Item", "FAKE", "https://example.com/fake", config, NO_ROYALTIES)
+          .accountsPartial({
+            auction: outsiderItem.auctionPda,
+            mint: outsiderMint.publicKey,
+            metadata: outsiderItem.metadataPda,
+            vault: outsiderItem.vault,
+            owner: outsider.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata: collection.metadataPda,
+            collectionMasterEdition: collection.masterEditionPda,
+          })
+          .signers([outsider, outsiderMint])
+          .rpc();
+        expect.fail("Should have failed");
+      } catch (error) {
+        expect(error.message).to.not.equal("Should have failed");
+      }
+    });
+  });
+
+  describe("Token Payments", () => {
+    it("Escrows, settles and refunds bids paid in an SPL token", async () => {
+      // A 6-decimal payment currency (e.g. USDC), each bidder holds 10_000_000 base units
+      const paymentMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const bidder3 = Keypair.generate();
+      await airdrop(provider.connection, bidder3.publicKey);
+      const paymentAccounts = new Map<string, PublicKey>();
+      for (const bidder of [bidder1, bidder2, bidder3]) {
+        const ata = await createAssociatedTokenAccount(provider.connection, owner, paymentMint, bidder.publicKey);
+        await mintTo(provider.connection, owner, paymentMint, ata, owner, 10_000_000);
+        paymentAccounts.set(bidder.publicKey.toBase58(), ata);
+      }
+      const paymentBalance = async (bidder: Keypair) =>
+        (await provider.connection.getTokenAccountBalance(paymentAccounts.get(bidder.publicKey.toBase58())))
+          .value.amount;
+
+      const tokenMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, tokenMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.keeperBountyBps = 100;
+
+      await program.methods
+        .createAuction("Token Payment Test", "TPAY", "https://example.com/tpay", config, NO_ROYALTIES)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          mint: tokenMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          paymentMint,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, tokenMint])
+        .rpc();
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.paymentMint.equals(paymentMint)).to.be.true;
+
+      const bidEscrow = (bidder: Keypair) =>
+        getAssociatedTokenAddress(paymentMint, bidPda(program.programId, accounts.auctionPda, bidder.publicKey), true);
+      const placeTokenBid = async (bidder: Keypair, amount: number) =>
+        program.methods
+          .placeBid(new anchor.BN(amount))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
+            paymentMint,
+            bidEscrow: await bidEscrow(bidder),
+            bidderPaymentAccount: paymentAccounts.get(bidder.publicKey.toBase58()),
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(amount)))
+          .signers([bidder])
+          .rpc();
+
+      // Token auctions need the payment accounts
+      try {
+        await program.methods
+          .placeBid(new anchor.BN(4_000_000))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(4_000_000)))
+          .signers([bidder1])
+          .rpc();
+        expect.fail("Should have failed with PaymentAccountsMissing");
+      } catch (error) {
+        expect(error.toString()).to.include("PaymentAccountsMissing");
+      }
+
+      await placeTokenBid(bidder3, 2_000_000);
+      await placeTokenBid(bidder1, 4_000_000);
+      await placeTokenBid(bidder2, 6_000_000);
+      expect(await paymentBalance(bidder3)).to.equal("8000000");
+      expect((await provider.connection.getTokenAccountBalance(await bidEscrow(bidder2))).value.amount).to.equal(
+        "6000000"
+      );
+
+      // Cancelling a bid refunds its token escrow and closes it
+      await program.methods
+        .cancelBid()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder3.publicKey,
+          paymentMint,
+          bidEscrow: await bidEscrow(bidder3),
+          bidderPaymentAccount: paymentAccounts.get(bidder3.publicKey.toBase58()),
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder3.publicKey, null))
+        .signers([bidder3])
+        .rpc();
+      expect(await paymentBalance(bidder3)).to.equal("10000000");
+      expect(await provider.connection.getAccountInfo(await bidEscrow(bidder3))).to.be.null;
+
+      // Raising only escrows the difference
+      await placeTokenBid(bidder2, 7_000_000);
+      expect(await paymentBalance(bidder2)).to.equal("3000000");
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      // Bidder 1 concludes and takes the 1% bounty in the payment token
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          winningBid: bidPda(program.programId, accounts.auctionPda, bidder2.publicKey),
+          paymentMint,
+          winningBidEscrow: await bidEscrow(bidder2),
+          crankerPaymentAccount: paymentAccounts.get(bidder1.publicKey.toBase58()),
+          tokenMint: tokenMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+      expect(await paymentBalance(bidder1)).to.equal("6070000");
+
+      const ownerPaymentAccount = await getAssociatedTokenAddress(paymentMint, owner.publicKey);
+      const winnerTokenAccount = await getAssociatedTokenAddress(tokenMint.publicKey, bidder2.publicKey);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: tokenMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder2.publicKey,
+          payer: bidder2.publicKey,
+          paymentMint,
+          bidEscrow: await bidEscrow(bidder2),
+          ownerPaymentAccount,
+          winnerPaymentAccount: paymentAccounts.get(bidder2.publicKey.toBase58()),
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder2])
+        .rpc();
+
+      // The owner receives the price less the bounty, the winner paid exactly the price
+      expect((await provider.connection.getTokenAccountBalance(ownerPaymentAccount)).value.amount).to.equal(
+        "6930000"
+      );
+      expect(await paymentBalance(bidder2)).to.equal("3000000");
+      expect((await provider.connection.getTokenAccountBalance(winnerTokenAccount)).value.amount).to.equal("1");
+
+      // A decoy escrow owned by the bid PDA cannot stand in for its payment ATA, neither in a refund
+      // claim nor in a settlement page: draining it would close the bid and strand the real escrow
+      const decoyMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const decoyEscrow = await createAccount(
+        provider.connection,
+        owner,
+        decoyMint,
+        bidPda(program.programId, accounts.auctionPda, bidder1.publicKey),
+        Keypair.generate()
+      );
+      try {
+        await program.methods
+          .claimRefund()
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bid: bidPda(program.programId, accounts.auctionPda, bidder1.publicKey),
+            bidder: bidder1.publicKey,
+            owner: owner.publicKey,
+            paymentMint,
+            bidEscrow: decoyEscrow,
+            bidderPaymentAccount: paymentAccounts.get(bidder1.publicKey.toBase58()),
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("Should have failed with ConstraintAssociated");
+      } catch (error) {
+        expect(error.toString()).to.include("ConstraintAssociated");
+      }
+      try {
+        await program.methods
+          .settleBatch()
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            owner: owner.publicKey,
+            paymentMint,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts(
+            [
+              ...bidAccounts(program.programId, accounts.auctionPda, [bidder1.publicKey]),
+              decoyEscrow,
+              paymentAccounts.get(bidder1.publicKey.toBase58()),
+            ].map((account) =>
+              account instanceof PublicKey ? { pubkey: account, isWritable: true, isSigner: false } : account
+            )
+          )
+          .rpc();
+        expect.fail("Should have failed with InvalidBidAccounts");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidBidAccounts");
+      }
+      expect((await provider.connection.getTokenAccountBalance(await bidEscrow(bidder1))).value.amount).to.equal(
+        "4000000"
+      );
+
+      // Losing token bids settle in batches too, each with its token escrow and payment account
+      await program.methods
+        .settleBatch()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          owner: owner.publicKey,
+          paymentMint,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          [
+            ...bidAccounts(program.programId, accounts.auctionPda, [bidder1.publicKey]),
+            await bidEscrow(bidder1),
+            paymentAccounts.get(bidder1.publicKey.toBase58()),
+          ].map((account) =>
+            account instanceof PublicKey ? { pubkey: account, isWritable: true, isSigner: false } : account
+          )
+        )
+        .rpc();
+
+      expect(await paymentBalance(bidder1)).to.equal("10070000");
+      expect(
+        await provider.connection.getAccountInfo(bidPda(program.programId, accounts.auctionPda, bidder1.publicKey))
+      ).to.be.null;
+      expect(await provider.connection.getAccountInfo(await bidEscrow(bidder1))).to.be.null;
+      expect(await provider.connection.getAccountInfo(accounts.auctionPda)).to.be.null;
+    });
+
+    it("Fails to take token payments for a buy-now auction", async () => {
+      const paymentMint = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const tokenMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, tokenMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.buyNowLamports = new anchor.BN(20_000_000);
+
+      try {
+        await program.methods
+          .createAuction("Token Payment Test", "TPAY", "https://example.com/tpay", config, NO_ROYALTIES)
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            mint: tokenMint.publicKey,
+            metadata: accounts.metadataPda,
+            vault: accounts.vault,
+            paymentMint,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([owner, tokenMint])
+          .rpc();
+        expect.fail("Should have failed with InvalidPaymentConfig");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidPaymentConfig");
+      }
+    });
+  });
+
+  describe("Token-2022", () => {
+    it("Auctions a transfer-fee token for bids paid in a transfer-fee token", async () => {
+      // Both mints withhold 1% of every transfer
+      const assetMint = await createTransferFeeMint(provider.connection, owner, 100);
+      const ownerAta = await createAssociatedTokenAccount(
+        provider.connection, owner, assetMint, owner.publicKey, undefined, TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(provider.connection, owner, assetMint, ownerAta, owner, 1_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      const paymentMint = await createTransferFeeMint(provider.connection, owner, 100);
+      const bidderPaymentAccount = await createAssociatedTokenAccount(
+        provider.connection, owner, paymentMint, bidder1.publicKey, undefined, TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(
+        provider.connection, owner, paymentMint, bidderPaymentAccount, owner, 10_000_000, [], undefined, TOKEN_2022_PROGRAM_ID
+      );
+
+      const accounts = await deriveAuctionAccounts(program.programId, assetMint, owner.publicKey, 0, TOKEN_2022_PROGRAM_ID);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      await program.methods
+        .createAuctionForExistingMint(new anchor.BN(100_000), config)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: assetMint,
+          ownerTokenAccount: ownerAta,
+          vault: accounts.vault,
+          paymentMint,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      // The fee is withheld from the deposit, the auction records what arrived
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.tokenAmount.toNumber()).to.equal(99_000);
+
+      const bidEscrow = await getAssociatedTokenAddress(
+        paymentMint,
+        bidPda(program.programId, accounts.auctionPda, bidder1.publicKey),
+        true,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .placeBid(new anchor.BN(1_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          paymentMint,
+          bidEscrow,
+          bidderPaymentAccount,
+          paymentTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(1_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      // The bidder pays the fee on top, so the escrow holds the full bid
+      expect((await provider.connection.getTokenAccountBalance(bidEscrow)).value.amount).to.equal("1000000");
+      const bidderBalance = Number((await provider.connection.getTokenAccountBalance(bidderPaymentAccount)).value.amount);
+      expect(10_000_000 - bidderBalance).to.be.greaterThan(1_010_000);
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: assetMint,
+          vault: accounts.vault,
+          ownerTokenAccount: ownerAta,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(assetMint, bidder1.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      const ownerPaymentAccount = await getAssociatedTokenAddress(paymentMint, owner.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: assetMint,
+          vault: accounts.vault,
+          winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          paymentMint,
+          bidEscrow,
+          ownerPaymentAccount,
+          winnerPaymentAccount: bidderPaymentAccount,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      // Each side receives its transfer less the 1% fee
+      expect((await provider.connection.getTokenAccountBalance(ownerPaymentAccount)).value.amount).to.equal("990000");
+      expect((await provider.connection.getTokenAccountBalance(winnerTokenAccount)).value.amount).to.equal("98010");
+
+      // Withheld fees were harvested to the mints, so the vault and escrow could close
+      expect(await provider.connection.getAccountInfo(accounts.vault)).to.be.null;
+      expect(await provider.connection.getAccountInfo(bidEscrow)).to.be.null;
+      expect(await provider.connection.getAccountInfo(accounts.auctionPda)).to.be.null;
+    });
+  });
+
+  describe("Token Metadata", () => {
+    it("Creates a Token-2022 auction with on-mint metadata and no Metaplex program", async () => {
+      const metadataMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(
+        program.programId, metadataMint.publicKey, owner.publicKey, 0, TOKEN_2022_PROGRAM_ID
+      );
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+
+      await program.methods
+        .createAuctionWithTokenMetadata("On-Mint Metadata", "META", "https://example.com/meta", config)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          mint: metadataMint.publicKey,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([owner, metadataMint])
+        .rpc();
+
+      // Name, symbol and uri live in the mint's token-metadata extension
+      const tokenMetadata = await getTokenMetadata(
+        provider.connection, metadataMint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenMetadata.name).to.equal("On-Mint Metadata");
+      expect(tokenMetadata.symbol).to.equal("META");
+      expect(tokenMetadata.uri).to.equal("https://example.com/meta");
+      expect(tokenMetadata.updateAuthority.equals(owner.publicKey)).to.be.true;
+
+      const auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.minted).to.be.true;
+      expect((await provider.connection.getTokenAccountBalance(accounts.vault)).value.amount).to.equal("1");
+
+      // The mint authority is revoked, so the token stays 1-of-1
+      const metadataMintInfo = await getMint(
+        provider.connection, metadataMint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID
+      );
+      expect(metadataMintInfo.supply.toString()).to.equal("1");
+      expect(metadataMintInfo.mintAuthority).to.be.null;
+      expect(metadataMintInfo.freezeAuthority).to.be.null;
+
+      await program.methods
+        .placeBid(new anchor.BN(2_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(2_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: metadataMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(
+        metadataMint.publicKey, bidder1.publicKey, false, TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: metadataMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      expect((await provider.connection.getTokenAccountBalance(winnerTokenAccount)).value.amount).to.equal("1");
+      expect(await provider.connection.getAccountInfo(accounts.auctionPda)).to.be.null;
+    });
+  });
+
+  describe("Proxy Bidding", () => {
+    it("Raises a proxy bid only as far as needed to stay on top", async () => {
+      const proxyMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, proxyMint.publicKey, owner.publicKey);
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.minIncrementLamports = new anchor.BN(500_000);
+
+      await program.methods
+        .createAuction("Proxy Test", "PRXY", "https://example.com/proxy", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: proxyMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, proxyMint])
+        .rpc();
+
+      // bidder1 escrows a 10M ceiling but only shows the minimum while unopposed
+      await program.methods
+        .placeProxyBid(new anchor.BN(10_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(10_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      let auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.bids[0].maxLamports.toNumber()).to.equal(10_000_000);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder1.publicKey.toString());
+      expect(auctionAccount.topCeiling.toNumber()).to.equal(10_000_000);
+      expect(auctionAccount.topLamports.toNumber()).to.equal(1);
+
+      // Each plain bid is answered with the next increment
+      for (const [bidder, lamports] of [[bidder2, 4_000_000], [bidder3, 5_000_000]] as [Keypair, number][]) {
+        await program.methods
+          .placeBid(new anchor.BN(lamports))
+          .accountsPartial({
+            auction: accounts.auctionPda,
+            bidder: bidder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder.publicKey, new anchor.BN(lamports)))
+          .signers([bidder])
+          .rpc();
+
+        auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+        expect(auctionAccount.topBidder.toString()).to.equal(bidder1.publicKey.toString());
+        expect(auctionAccount.topLamports.toNumber()).to.equal(lamports + 500_000);
+      }
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(proxyMint.publicKey, bidder1.publicKey);
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          owner.publicKey,
+          winnerTokenAccount,
+          bidder1.publicKey,
+          proxyMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx, [owner]);
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      const auctionRent = await accountRent(provider.connection, accounts.auctionPda);
+      const vaultRent = await accountRent(provider.connection, accounts.vault);
+      const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+      const bidRent = await accountRent(provider.connection, bidPda(program.programId, accounts.auctionPda, bidder1.publicKey));
+      const bidder1BalanceBefore = await provider.connection.getBalance(bidder1.publicKey);
+
+      await program.methods
+        .concludeAuction(null)
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: proxyMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: proxyMint.publicKey,
+          vault: accounts.vault,
+          winnerTokenAccount: winnerTokenAccount,
+          owner: owner.publicKey,
+          winner: bidder1.publicKey,
+          payer: bidder1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bidder1])
+        .rpc();
+
+      await claimRefunds(program, accounts.auctionPda, owner.publicKey, [
+        bidder2.publicKey,
+        bidder3.publicKey,
+      ]);
+
+      // Owner received the visible proxy bid (less the auction record rent of their conclusion),
+      // the unused ceiling went back to bidder1
+      const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+      const expectedIncrease = 5_500_000 + auctionRent + vaultRent - (await recordRent(provider.connection));
+      expect(ownerBalanceAfter - ownerBalanceBefore).to.be.closeTo(expectedIncrease, 10_000);
+
+      const bidder1BalanceAfter = await provider.connection.getBalance(bidder1.publicKey);
+      expect(bidder1BalanceAfter - bidder1BalanceBefore).to.equal(4_500_000 + bidRent);
+
+      const winnerTokenBalance = await provider.connection.getTokenAccountBalance(winnerTokenAccount);
+      expect(winnerTokenBalance.value.amount).to.equal("1");
+    });
+
+    it("Raises a proxy leader to a hidden reserve revealed at conclusion", async () => {
+      const proxyMint = Keypair.generate();
+      const accounts = await deriveAuctionAccounts(program.programId, proxyMint.publicKey, owner.publicKey);
+      const reserveLamports = new anchor.BN(5_000_000);
+      const salt = Keypair.generate().publicKey.toBytes();
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      config.reserve = { hidden: { commitment: saltedCommitment(reserveLamports, salt) } };
+
+      await program.methods
+        .createAuction("Proxy Reserve Test", "PRSV", "https://example.com/proxy-reserve", config, NO_ROYALTIES)
+        .accounts({
+          auction: accounts.auctionPda,
+          mint: proxyMint.publicKey,
+          metadata: accounts.metadataPda,
+          vault: accounts.vault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, proxyMint])
+        .rpc();
+
+      // bidder1 escrows a 10M ceiling, bidder2 only bids 1M
+      await program.methods
+        .placeProxyBid(new anchor.BN(10_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder1.publicKey, new anchor.BN(10_000_000)))
+        .signers([bidder1])
+        .rpc();
+      await program.methods
+        .placeBid(new anchor.BN(1_000_000))
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          bidder: bidder2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, accounts.auctionPda, bidder2.publicKey, new anchor.BN(1_000_000)))
+        .signers([bidder2])
+        .rpc();
+
+      // The hidden reserve is unknown while bidding, so the proxy only shows the runner-up plus one
+      let auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.topLamports.toNumber()).to.equal(1_000_001);
+
+      await waitForAuctionEnd(provider.connection, accounts.auctionPda);
+
+      await program.methods
+        .concludeAuction({ lamports: reserveLamports, salt: Array.from(salt) })
+        .accountsPartial({
+          auction: accounts.auctionPda,
+          tokenMint: proxyMint.publicKey,
+          vault: accounts.vault,
+          ownerTokenAccount: accounts.ownerTokenAccount,
+          owner: owner.publicKey,
+          cranker: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner])
+        .rpc();
+
+      // The ceiling covers the revealed reserve, so the proxy leader wins at the reserve
+      auctionAccount = await readAuction(provider.connection, accounts.auctionPda);
+      expect(auctionAccount.outcome).to.equal(AUCTION_OUTCOME_BID_WON);
+      expect(auctionAccount.topBidder.toString()).to.equal(bidder1.publicKey.toString());
+      expect(auctionAccount.topLamports.toNumber()).to.equal(5_000_000);
+      expect(auctionAccount.saleLamports.toNumber()).to.equal(5_000_000);
+    });
+  });
+
+  describe("Conclude Auction - No Bidders", () => {
+    it("Fails to conclude auction immediately after creation (no bids)", async () => {
+      // Create a new auction for this test
+      const noBidsMint = Keypair.generate();
+      
+      const [noBidsAuctionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("auction"), noBidsMint.publicKey.toBuffer(), auctionNonce(0)],
+        program.programId
+      );
+
+      const noBidsOwnerTokenAccount = await getAssociatedTokenAddress(
+        noBidsMint.publicKey,
+        owner.publicKey
+      );
+      const noBidsVault = await getAssociatedTokenAddress(noBidsMint.publicKey, noBidsAuctionPda, true);
+
+      const [noBidsMetadataPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          noBidsMint.publicKey.toBuffer(),
+        ],
+        MPL_TOKEN_METADATA_PROGRAM_ID
+      );
+
+      // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      await program.methods
+        .createAuction("No Bids Token", "NOBID", "https://nobids.uri", config, NO_ROYALTIES)
+        .accounts({
+          auction: noBidsAuctionPda,
+          mint: noBidsMint.publicKey,
+          metadata: noBidsMetadataPda,
+          vault: noBidsVault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, noBidsMint])
+        .rpc();
+
+      // Try to conclude auction before its end time
+      try {
+        await program.methods
+          .concludeAuction(null)
+          .accountsPartial({
+            auction: noBidsAuctionPda,
+            tokenMint: noBidsMint.publicKey,
+            vault: noBidsVault,
+            ownerTokenAccount: noBidsOwnerTokenAccount,
+            owner: owner.publicKey,
+            cranker: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([owner])
+          .rpc();
+
+        expect.fail("Should have failed with AuctionNotEnded");
+      } catch (error) {
+        expect(error.toString()).to.include("AuctionNotEnded");
+      }
+
+      await waitForAuctionEnd(provider.connection, noBidsAuctionPda);
+
+      // Try to conclude auction with no bids
+      try {
+        await program.methods
+          .concludeAuction(null)
+          .accountsPartial({
+            auction: noBidsAuctionPda,
+            tokenMint: noBidsMint.publicKey,
+            vault: noBidsVault,
+            ownerTokenAccount: noBidsOwnerTokenAccount,
+            owner: owner.publicKey,
+            cranker: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([owner])
+          .rpc();
+
+        expect.fail("Should have failed with NoActiveBids");
+      } catch (error) {
+        expect(error.toString()).to.include("NoActiveBids");
+      }
+
+      // Clean up - cancel auction
+      await program.methods
+        .cancelAuction()
+        .accountsPartial({
+          auction: noBidsAuctionPda,
+          tokenMint: noBidsMint.publicKey,
+          vault: noBidsVault,
+          ownerTokenAccount: noBidsOwnerTokenAccount,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Fails to conclude auction after bid is placed and cancelled", async () => {
+      // Create another auction for this test
+      const cancelledBidMint = Keypair.generate();
+      
+      const [cancelledBidAuctionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("auction"), cancelledBidMint.publicKey.toBuffer(), auctionNonce(0)],
+        program.programId
+      );
+
+      const cancelledBidOwnerTokenAccount = await getAssociatedTokenAddress(
+        cancelledBidMint.publicKey,
+        owner.publicKey
+      );
+      const cancelledBidVault = await getAssociatedTokenAddress(cancelledBidMint.publicKey, cancelledBidAuctionPda, true);
+
+      const [cancelledBidMetadataPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          cancelledBidMint.publicKey.toBuffer(),
+        ],
+        MPL_TOKEN_METADATA_PROGRAM_ID
+      );
+
+      // Create auction
+      const config = await auctionConfig(provider.connection, SHORT_AUCTION_SECS);
+      await program.methods
+        .createAuction("Cancelled Bid Token", "CANCEL", "https://cancelled.uri", config, NO_ROYALTIES)
+        .accounts({
+          auction: cancelledBidAuctionPda,
+          mint: cancelledBidMint.publicKey,
+          metadata: cancelledBidMetadataPda,
+          vault: cancelledBidVault,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([owner, cancelledBidMint])
+        .rpc();
+
+      // Place a bid
+      await program.methods
+        .placeBid(new anchor.BN(5_000_000))
+        .accountsPartial({
+          auction: cancelledBidAuctionPda,
+          bidder: bidder1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await rankingAccounts(program, cancelledBidAuctionPda, bidder1.publicKey, new anchor.BN(5_000_000)))
+        .signers([bidder1])
+        .rpc();
+
+      // Verify bid was placed
+      let auctionAccount = await readAuction(provider.connection, cancelledBidAuctionPda);
+      expect(auctionAccount.bidCount).to.equal(1);
+
+      // Cancel the bid
+      await program.methods
+        .cancelBid()
+        .accountsPartial({
+          auction: cancelledBidAuctionPda,
+          bidder: bidder1.publicKey,
+        })
+        .remainingAccounts(await rankingAccounts(program, cancelledBidAuctionPda, bidder1.publicKey, null))
+        .signers([bidder1])
+        .rpc();
+
+      // Verify bid was cancelled
+      auctionAccount = await readAuction(provider.connection, cancelledBidAuctionPda);
+      expect(auctionAccount.bidCount).to.equal(0);
+
+      await waitForAuctionEnd(provider.connection, cancelledBidAuctionPda);
+
+      // Try to conclude auction with no active bids
+      try {
+        await program.methods
+          .concludeAuction(null)
+          .accountsPartial({
+            auction: cancelledBidAuctionPda,
+            tokenMint: cancelledBidMint.publicKey,
+            vault: cancelledBidVault,
+            ownerTokenAccount: cancelledBidOwnerTokenAccount,
+            owner: owner.publicKey,
+            cranker: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([owner])
           .rpc();
 
         expect.fail("Should have failed with NoActiveBids");
@@ -1545,6 +5094,7 @@ describe("bidding_system", () => {
         .accountsPartial({
           auction: cancelledBidAuctionPda,
           tokenMint: cancelledBidMint.publicKey,
+          vault: cancelledBidVault,
           ownerTokenAccount: cancelledBidOwnerTokenAccount,
           owner: owner.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
@@ -1568,3 +5118,221 @@ async function airdrop(
   );
 }
 
+// Helper to encode an auction nonce as its PDA seed (u64 little-endian)
+function auctionNonce(nonce: number): Buffer {
+  return new anchor.BN(nonce).toArrayLike(Buffer, "le", 8);
+}
+
+// Helper to derive the auction PDA (the mint's nonce-th auction), owner ATA, token vault and
+// metadata PDA for a mint of the given token program
+async function deriveAuctionAccounts(
+  programId: PublicKey,
+  mint: PublicKey,
+  owner: PublicKey,
+  nonce = 0,
+  tokenProgram = TOKEN_PROGRAM_ID
+) {
+  const [auctionPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("auction"), mint.toBuffer(), auctionNonce(nonce)],
+    programId
+  );
+  const ownerTokenAccount = await getAssociatedTokenAddress(mint, owner, false, tokenProgram);
+  const vault = await getAssociatedTokenAddress(mint, auctionPda, true, tokenProgram);
+  const [metadataPda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("metadata"),
+      MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+    ],
+    MPL_TOKEN_METADATA_PROGRAM_ID
+  );
+  const [masterEditionPda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("metadata"),
+      MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+      Buffer.from("edition"),
+    ],
+    MPL_TOKEN_METADATA_PROGRAM_ID
+  );
+  return { auctionPda, ownerTokenAccount, vault, metadataPda, masterEditionPda };
+}
+
+// Helper to read the collection fields of a Metaplex metadata account (borsh layout)
+async function readMetadataCollection(connection: anchor.web3.Connection, metadataPda: PublicKey) {
+  const data = (await connection.getAccountInfo(metadataPda)).data;
+  // key(1) + update_authority(32) + mint(32), then name, symbol and uri as u32-prefixed strings
+  let offset = 65;
+  for (let i = 0; i < 3; i++) {
+    offset += 4 + data.readUInt32LE(offset);
+  }
+  // seller_fee_basis_points(2) + creators: Option<Vec<Creator(34)>>
+  offset += 2;
+  if (data.readUInt8(offset++) === 1) {
+    offset += 4 + data.readUInt32LE(offset) * 34;
+  }
+  // primary_sale_happened(1) + is_mutable(1) + edition_nonce: Option<u8> + token_standard: Option<u8>
+  offset += 2;
+  for (let i = 0; i < 2; i++) {
+    offset += data.readUInt8(offset) === 1 ? 2 : 1;
+  }
+  // collection: Option<{ verified(1), key(32) }>
+  let collection = null;
+  if (data.readUInt8(offset++) === 1) {
+    collection = {
+      verified: data.readUInt8(offset) === 1,
+      key: new PublicKey(data.subarray(offset + 1, offset + 33)),
+    };
+    offset += 33;
+  }
+  // uses: Option<{ use_method(1), remaining(8), total(8) }>
+  offset += data.readUInt8(offset) === 1 ? 18 : 1;
+  // collection_details: Option<V1 { size(8) }>
+  const collectionSize =
+    data.readUInt8(offset) === 1 ? Number(data.readBigUInt64LE(offset + 2)) : null;
+  return { collection, collectionSize };
+}
+
+// Helper to create a 6-decimal Token-2022 mint withholding `feeBps` of every transfer
+async function createTransferFeeMint(
+  connection: anchor.web3.Connection,
+  authority: Keypair,
+  feeBps: number
+): Promise<PublicKey> {
+  const mint = Keypair.generate();
+  const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+  const transaction = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: authority.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space: mintLen,
+      lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    createInitializeTransferFeeConfigInstruction(
+      mint.publicKey,
+      authority.publicKey,
+      authority.publicKey,
+      feeBps,
+      BigInt(1_000_000_000),
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createInitializeMintInstruction(mint.publicKey, 6, authority.publicKey, null, TOKEN_2022_PROGRAM_ID)
+  );
+  await sendAndConfirmTransaction(connection, transaction, [authority, mint]);
+  return mint.publicKey;
+}
+
+// Helper to derive a bidder's bid PDA
+function bidPda(programId: PublicKey, auctionPda: PublicKey, bidder: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("bid"), auctionPda.toBuffer(), bidder.toBuffer()],
+    programId
+  );
+  return pda;
+}
+
+// Helper to derive the provenance record PDA of an auction
+function recordPda(programId: PublicKey, auctionPda: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("auction_record"), auctionPda.toBuffer()],
+    programId
+  );
+  return pda;
+}
+
+// Helper to get the rent of an auction record
+async function recordRent(connection: anchor.web3.Connection): Promise<number> {
+  return await connection.getMinimumBalanceForRentExemption(AUCTION_RECORD_SIZE);
+}
+
+// Helper to build the [bid, bidder] remaining accounts of a settle_batch page
+function bidAccounts(programId: PublicKey, auctionPda: PublicKey, bidders: PublicKey[]) {
+  return bidders.flatMap((bidder) => [
+    { pubkey: bidPda(programId, auctionPda, bidder), isWritable: true, isSigner: false },
+    { pubkey: bidder, isWritable: true, isSigner: false },
+  ]);
+}
+
+// Helper to claim the refund of each bidder's bid once an auction is concluded or cancelled
+async function claimRefunds(
+  program: Program<BiddingSystem>,
+  auctionPda: PublicKey,
+  owner: PublicKey,
+  bidders: PublicKey[]
+) {
+  for (const bidder of bidders) {
+    await program.methods
+      .claimRefund()
+      .accountsPartial({
+        auction: auctionPda,
+        bid: bidPda(program.programId, auctionPda, bidder),
+        bidder,
+        owner,
+      })
+      .rpc();
+  }
+}
+
+// Helper to get the rent-exempt balance held by an account (returned when it closes)
+async function accountRent(connection: anchor.web3.Connection, address: PublicKey): Promise<number> {
+  const accountInfo = await connection.getAccountInfo(address);
+  return await connection.getMinimumBalanceForRentExemption(accountInfo.data.length);
+}
+
+// Helper to build a hidden reserve or sealed bid commitment: sha256(lamports_le || salt)
+function saltedCommitment(lamports: anchor.BN, salt: Uint8Array): number[] {
+  const hash = createHash("sha256");
+  hash.update(lamports.toArrayLike(Buffer, "le", 8));
+  hash.update(salt);
+  return Array.from(hash.digest());
+}
+
+// Helper to get the current cluster unix timestamp
+async function clusterTime(connection: anchor.web3.Connection): Promise<number> {
+  return await connection.getBlockTime(await connection.getSlot("confirmed"));
+}
+
+// Helper to build an auction config that opens now and lasts durationSecs (no soft close)
+async function auctionConfig(connection: anchor.web3.Connection, durationSecs: number) {
+  const now = await clusterTime(connection);
+  return {
+    startTs: new anchor.BN(now),
+    endTs: new anchor.BN(now + durationSecs),
+    extensionWindowSecs: new anchor.BN(0),
+    extensionSecs: new anchor.BN(0),
+    maxEndTs: new anchor.BN(now + durationSecs),
+    reserve: { none: {} } as any,
+    minIncrementLamports: new anchor.BN(0),
+    minIncrementBps: 0,
+    buyNowLamports: new anchor.BN(0),
+    kind: { english: {} } as any,
+    settlement: { firstPrice: {} } as any,
+    keeperBountyBps: 0,
+    gracePeriodSecs: new anchor.BN(MIN_GRACE_PERIOD_SECS),
+  };
+}
+
+// Helper to wait until an auction's bidding window has closed on-chain
+async function waitForAuctionEnd(connection: anchor.web3.Connection, auctionPda: PublicKey) {
+  const { endTs } = await readAuction(connection, auctionPda);
+  while ((await clusterTime(connection)) <= endTs.toNumber()) {
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+  }
+}
+
+// Helper to wait until an auction's grace period after the bidding window has passed on-chain
+async function waitForGracePeriod(connection: anchor.web3.Connection, auctionPda: PublicKey) {
+  const { endTs, gracePeriodSecs } = await readAuction(connection, auctionPda);
+  while ((await clusterTime(connection)) <= endTs.add(gracePeriodSecs).toNumber()) {
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+  }
+}
+
+// Helper to wait until a sealed auction's reveal phase has closed on-chain
+async function waitForRevealEnd(connection: anchor.web3.Connection, auctionPda: PublicKey) {
+  const { revealEndTs } = await readAuction(connection, auctionPda);
+  while ((await clusterTime(connection)) <= revealEndTs.toNumber()) {
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+  }
+}
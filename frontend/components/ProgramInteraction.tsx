@@ -5,7 +5,70 @@ import { useState } from 'react';
 import { getProgram } from '@/utils/anchorClient';
 import { PublicKey, Keypair } from '@solana/web3.js';
 import { getAssociatedTokenAddress } from '@solana/spl-token';
-import { BN } from '@coral-xyz/anchor';
+import { BN, Program } from '@coral-xyz/anchor';
+
+const METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+const TOKEN_PROGRAM_ID = new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA');
+const SYSTEM_PROGRAM_ID = new PublicKey('11111111111111111111111111111111');
+
+// Shortest grace period the program accepts (MIN_GRACE_PERIOD_SECS)
+const MIN_GRACE_PERIOD_SECS = 60;
+
+// Auction nonce as used in the auction PDA seeds (u64, little endian)
+const auctionNonce = (nonce: BN) => nonce.toArrayLike(Buffer, 'le', 8);
+
+// Bid PDA of a bidder in an auction
+const bidPda = (program: Program, auction: PublicKey, bidder: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from('bid'), auction.toBytes(), bidder.toBytes()],
+    program.programId
+  )[0];
+
+// Neighbouring bid accounts that a bid update relinks in the auction's ranking: the bid ranked
+// above the bidder's current bid (the runner-up if it leads) and the lowest other bid ranked at
+// or above its new escrow `rank` (null when the bid is cancelled)
+const rankingAccounts = async (
+  program: Program,
+  auction: PublicKey,
+  bidder: PublicKey,
+  rank: BN | null
+) => {
+  const auctionData = await (program as any).account.auction.fetch(auction);
+  const bids = await (program as any).account.bid.all([
+    { memcmp: { offset: 8, bytes: auction.toBase58() } },
+  ]);
+  const byBidder = new Map<string, any>(bids.map((bid: any) => [bid.account.bidder.toBase58(), bid]));
+
+  // Walk the ranking down from the leader
+  const ranking: any[] = [];
+  for (let next: PublicKey = auctionData.topBidder; !next.equals(PublicKey.default); ) {
+    const bid = byBidder.get(next.toBase58());
+    ranking.push(bid);
+    next = bid.account.nextBidder;
+  }
+
+  const neighbours = new Set<string>();
+  const index = ranking.findIndex(bid => bid.account.bidder.equals(bidder));
+  if (index > 0) {
+    neighbours.add(ranking[index - 1].publicKey.toBase58());
+  } else if (index === 0 && ranking.length > 1) {
+    neighbours.add(ranking[1].publicKey.toBase58());
+  }
+  if (rank !== null) {
+    const above = ranking.filter(
+      (bid, i) => i !== index && BN.max(bid.account.lamports, bid.account.maxLamports).gte(rank)
+    );
+    if (above.length > 0) {
+      neighbours.add(above[above.length - 1].publicKey.toBase58());
+    }
+  }
+
+  return [...neighbours].map(address => ({
+    pubkey: new PublicKey(address),
+    isWritable: true,
+    isSigner: false,
+  }));
+};
 
 export const ProgramInteraction = () => {
   const { publicKey, wallet } = useWallet();
@@ -17,6 +80,7 @@ export const ProgramInteraction = () => {
   const [auctionName, setAuctionName] = useState('');
   const [auctionSymbol, setAuctionSymbol] = useState('');
   const [auctionUri, setAuctionUri] = useState('');
+  const [auctionDurationSecs, setAuctionDurationSecs] = useState('3600');
 
   // Bid fields
   const [bidLamports, setBidLamports] = useState('');
@@ -42,40 +106,52 @@ export const ProgramInteraction = () => {
 
       // Derive metadata account (Metaplex standard)
       const [metadata] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from('metadata'),
-          new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s').toBytes(),
-          mint.publicKey.toBytes(),
-        ],
-        new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s')
+        [Buffer.from('metadata'), METADATA_PROGRAM_ID.toBytes(), mint.publicKey.toBytes()],
+        METADATA_PROGRAM_ID
       );
 
-      // Derive associated token account
-      const tokenAccount = await getAssociatedTokenAddress(mint.publicKey, publicKey);
-
-      // Derive auction PDA
+      // Derive auction PDA: a new mint's first auction takes nonce 0 from its auction counter
       const [auction] = PublicKey.findProgramAddressSync(
-        [Buffer.from('auction'), mint.publicKey.toBytes()],
+        [Buffer.from('auction'), mint.publicKey.toBytes(), auctionNonce(new BN(0))],
         program.programId
       );
 
+      // English auction opening now, with no reserve, increment, buy-it-now or keeper bounty
+      const now = Math.floor(Date.now() / 1000);
+      const endTs = new BN(now + Number(auctionDurationSecs));
+      const config = {
+        startTs: new BN(now),
+        endTs,
+        extensionWindowSecs: new BN(0),
+        extensionSecs: new BN(0),
+        maxEndTs: endTs,
+        reserve: { none: {} },
+        minIncrementLamports: new BN(0),
+        minIncrementBps: 0,
+        buyNowLamports: new BN(0),
+        kind: { english: {} },
+        settlement: { firstPrice: {} },
+        keeperBountyBps: 0,
+        gracePeriodSecs: new BN(MIN_GRACE_PERIOD_SECS),
+      };
+      const royalties = { sellerFeeBasisPoints: 0, creators: [] };
+
       const tx = await program.methods
-        .createAuction(auctionName, auctionSymbol, auctionUri)
-        .accounts({
+        .createAuction(auctionName, auctionSymbol, auctionUri, config, royalties)
+        .accountsPartial({
           auction,
           mint: mint.publicKey,
           metadata,
-          tokenAccount,
+          paymentMint: null,
+          collectionMint: null,
+          collectionMetadata: null,
+          collectionMasterEdition: null,
           owner: publicKey,
-          tokenProgram: new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
-          associatedTokenProgram: new PublicKey('ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL'),
-          metadataProgram: new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s'),
-          systemProgram: new PublicKey('11111111111111111111111111111111'),
-          rent: new PublicKey('SysvarRent111111111111111111111111111111111'),
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([mint])
         .rpc();
-      setStatus(`Auction created! Transaction: ${tx}`);
+      setStatus(`Auction ${auction.toBase58()} created! Transaction: ${tx}`);
     } catch (error) {
       console.error('Error:', error);
       const errMsg = error instanceof Error ? error.message : String(error);
@@ -99,10 +175,16 @@ export const ProgramInteraction = () => {
 
       const tx = await program.methods
         .placeBid(lamports)
-        .accounts({
+        .accountsPartial({
           auction,
           bidder: publicKey,
+          paymentMint: null,
+          bidEscrow: null,
+          bidderPaymentAccount: null,
+          paymentTokenProgram: null,
+          associatedTokenProgram: null,
         })
+        .remainingAccounts(await rankingAccounts(program, auction, publicKey, lamports))
         .rpc();
       setStatus(`Bid placed! Transaction: ${tx}`);
     } catch (error) {
@@ -127,10 +209,15 @@ export const ProgramInteraction = () => {
 
       const tx = await program.methods
         .cancelBid()
-        .accounts({
+        .accountsPartial({
           auction,
           bidder: publicKey,
+          paymentMint: null,
+          bidEscrow: null,
+          bidderPaymentAccount: null,
+          paymentTokenProgram: null,
         })
+        .remainingAccounts(await rankingAccounts(program, auction, publicKey, null))
         .rpc();
       setStatus(`Bid cancelled! Transaction: ${tx}`);
     } catch (error) {
@@ -153,40 +240,38 @@ export const ProgramInteraction = () => {
       const program = getProgram(wallet.adapter as any);
       const auction = new PublicKey(concludeAuctionAddress);
 
-      // Fetch auction data to get tokenMint and owner
+      // Fetch auction data to get tokenMint, owner and the top bidder
       const auctionData = await (program as any).account.auction.fetch(auction);
       const tokenMint = auctionData.tokenMint;
       const owner = auctionData.owner;
+      const topBidder: PublicKey = auctionData.topBidder;
 
-      // Find the winner (highest bid)
-      let winner = null;
-      let maxBid = 0;
-      for (const bid of auctionData.bids) {
-        if (bid.lamports > maxBid) {
-          maxBid = bid.lamports;
-          winner = bid.bidder;
-        }
-      }
-      if (!winner) {
-        throw new Error('No bids found in auction');
-      }
-
-      // Derive token accounts
+      // Derive owner token account (receives the token back if nobody wins)
       const ownerTokenAccount = await getAssociatedTokenAddress(tokenMint, owner);
-      const winnerTokenAccount = await getAssociatedTokenAddress(tokenMint, winner);
 
+      // The winning bid pays the keeper bounty and the royalties of a resale to its creators
+      const winningBid = topBidder.equals(PublicKey.default) ? null : bidPda(program, auction, topBidder);
+      const creators = auctionData.royaltyCreators
+        .slice(0, auctionData.royaltyCreatorCount)
+        .map((creator: PublicKey) => ({ pubkey: creator, isWritable: true, isSigner: false }));
+
+      // Anyone may conclude: the connected wallet cranks it and pays the auction record's rent
       const tx = await program.methods
-        .concludeAuction()
-        .accounts({
+        .concludeAuction(null)
+        .accountsPartial({
           auction,
+          winningBid,
+          paymentMint: null,
+          winningBidEscrow: null,
+          crankerPaymentAccount: null,
           tokenMint,
           ownerTokenAccount,
-          winnerTokenAccount,
           owner,
-          tokenProgram: new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
-          associatedTokenProgram: new PublicKey('ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL'),
-          systemProgram: new PublicKey('11111111111111111111111111111111'),
+          cranker: publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: null,
         })
+        .remainingAccounts(creators)
         .rpc();
       setStatus(`Auction concluded! Transaction: ${tx}`);
     } catch (error) {
@@ -198,6 +283,50 @@ export const ProgramInteraction = () => {
     }
   };
 
+  const handleClaimItem = async () => {
+    if (!publicKey || !wallet) {
+      setStatus('Please connect your wallet');
+      return;
+    }
+    try {
+      setLoading(true);
+      setStatus('Delivering item...');
+      const program = getProgram(wallet.adapter as any);
+      const auction = new PublicKey(concludeAuctionAddress);
+
+      // Fetch auction data to get tokenMint, owner and winner
+      const auctionData = await (program as any).account.auction.fetch(auction);
+      const tokenMint = auctionData.tokenMint;
+      const owner = auctionData.owner;
+      const winner = auctionData.topBidder;
+
+      // Anyone may deliver the token to the winner, paying the price to the owner
+      const tx = await program.methods
+        .claimItem()
+        .accountsPartial({
+          auction,
+          tokenMint,
+          owner,
+          winner,
+          payer: publicKey,
+          paymentMint: null,
+          bidEscrow: null,
+          ownerPaymentAccount: null,
+          winnerPaymentAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: null,
+        })
+        .rpc();
+      setStatus(`Item delivered! Transaction: ${tx}`);
+    } catch (error) {
+      console.error('Error:', error);
+      const errMsg = error instanceof Error ? error.message : String(error);
+      setStatus(`Error: ${errMsg}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
   const handleCancelAuction = async () => {
     if (!publicKey || !wallet) {
       setStatus('Please connect your wallet');
@@ -224,8 +353,8 @@ export const ProgramInteraction = () => {
           tokenMint,
           ownerTokenAccount,
           owner,
-          tokenProgram: new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
-          systemProgram: new PublicKey('11111111111111111111111111111111'),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SYSTEM_PROGRAM_ID,
         })
         .rpc();
       setStatus(`Auction cancelled! Transaction: ${tx}`);
@@ -275,6 +404,13 @@ export const ProgramInteraction = () => {
                 onChange={e => setAuctionUri(e.target.value)}
                 className="w-full border rounded px-3 py-2 mb-1"
               />
+              <input
+                type="number"
+                placeholder="Duration (seconds)"
+                value={auctionDurationSecs}
+                onChange={e => setAuctionDurationSecs(e.target.value)}
+                className="w-full border rounded px-3 py-2 mb-1"
+              />
               <button
                 onClick={handleCreateAuction}
                 disabled={loading}
@@ -346,6 +482,13 @@ export const ProgramInteraction = () => {
               >
                 {loading ? 'Processing...' : 'Conclude Auction'}
               </button>
+              <button
+                onClick={handleClaimItem}
+                disabled={loading}
+                className="w-full bg-teal-600 hover:bg-teal-700 text-white font-bold py-2 px-4 rounded disabled:opacity-50 disabled:cursor-not-allowed"
+              >
+                {loading ? 'Processing...' : 'Deliver Item to Winner'}
+              </button>
             </div>
 
             {/* Cancel Auction */}